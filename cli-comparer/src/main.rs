@@ -1,17 +1,34 @@
-use anyhow::{Context, Result};
+mod report;
+
+use anyhow::{bail, Context, Result};
 use clap::{Parser, ValueEnum};
-use parser::{
-    RecordParser, TransactionRecord, YPBankBinRecords, YPBankCsvRecords, YPBankTxtRecords,
-};
-use std::collections::HashMap;
-use std::fs::File;
+use cli_support::FileFormat;
+use parser::{Format, TransactionRecord};
+use report::{Duplicate, Duplicates, Entry, Field, FieldChange, Report, ReportFormat};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::stdout;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum DuplicatePolicy {
+    /// Fail if a file contains duplicate TX_ID values
+    Error,
+    /// Compare only the first record with each TX_ID
+    First,
+    /// Compare only the last record with each TX_ID
+    Last,
+    /// Compare all records with each TX_ID as a multiset
+    All,
+}
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
-enum FileFormat {
-    Csv,
-    Txt,
-    Binary,
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+#[value(rename_all = "snake_case")]
+enum SortKey {
+    /// Sort by TX_ID
+    TxId,
+    /// Sort by TIMESTAMP of the record, then by TX_ID
+    Timestamp,
 }
 
 #[derive(Parser)]
@@ -23,124 +40,819 @@ struct Cli {
     #[arg(long = "file2")]
     file2: PathBuf,
 
+    /// Format of the first file; detected from the file contents when omitted
     #[arg(long = "format1", value_enum)]
-    format1: FileFormat,
+    format1: Option<FileFormat>,
 
+    /// Format of the second file; detected from the file contents when omitted
     #[arg(long = "format2", value_enum)]
-    format2: FileFormat,
+    format2: Option<FileFormat>,
+
+    /// Format of the comparison report
+    #[arg(long = "report-format", value_enum, default_value_t = ReportFormat::Text)]
+    report_format: ReportFormat,
+
+    /// Field excluded from the comparison; may be repeated
+    #[arg(
+        long = "ignore-field",
+        value_enum,
+        value_name = "FIELD",
+        conflicts_with = "only_fields"
+    )]
+    ignore_fields: Vec<Field>,
+
+    /// Comma-separated list of the only fields to compare
+    #[arg(
+        long = "only-fields",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "FIELDS"
+    )]
+    only_fields: Vec<Field>,
+
+    /// How to compare records whose TX_ID occurs several times in one file
+    #[arg(long, value_enum, default_value_t = DuplicatePolicy::Error)]
+    duplicates: DuplicatePolicy,
+
+    /// Order of the reported differences
+    #[arg(long = "sort-by", value_enum, default_value_t = SortKey::TxId)]
+    sort_by: SortKey,
+
+    /// Also report records that appear in a different sequence in the two files
+    #[arg(long = "check-order")]
+    check_order: bool,
+
+    /// Print nothing; report the result only with the exit status
+    #[arg(short, long, conflicts_with = "summary")]
+    quiet: bool,
+
+    /// Print only the counts of missing and differing records
+    #[arg(long)]
+    summary: bool,
 }
 
-fn read_records(path: &Path, format: FileFormat, arg_name: &str) -> Result<Vec<TransactionRecord>> {
-    let mut file = File::open(path).with_context(|| {
-        format!(
-            "Failed to open input file '{}' specified in '--{}' argument",
+impl Cli {
+    fn compare_options(&self) -> CompareOptions {
+        // Поля, участвующие в сравнении, в порядке вывода
+        let fields = Field::ALL
+            .into_iter()
+            .filter(|field| self.only_fields.is_empty() || self.only_fields.contains(field))
+            .filter(|field| !self.ignore_fields.contains(field))
+            .collect();
+        CompareOptions {
+            fields,
+            duplicates: self.duplicates,
+            sort_by: self.sort_by,
+            check_order: self.check_order,
+        }
+    }
+}
+
+struct CompareOptions {
+    fields: Vec<Field>,
+    duplicates: DuplicatePolicy,
+    sort_by: SortKey,
+    check_order: bool,
+}
+
+fn read_records(
+    path: &Path,
+    format: Option<FileFormat>,
+    arg_name: &str,
+) -> Result<Vec<TransactionRecord>> {
+    let (format, file) = cli_support::open_detected(path, format.map(Format::from))
+        .with_context(|| format!("Invalid input file in '--{}' argument", arg_name))?;
+
+    let records = parser::record_reader(format, file)
+        .collect::<parser::ParseResult<Vec<TransactionRecord>>>()
+        .with_context(|| {
+            format!(
+                "Failed to parse as {} data from file '{}' (value of '--{}' argument)",
+                format,
+                path.display(),
+                arg_name
+            )
+        })?;
+    Ok(records)
+}
+
+// Записи файла с одним TX_ID вместе с их номерами (с 1)
+type Group<'a> = Vec<(usize, &'a TransactionRecord)>;
+
+// Записи файла, сгруппированные по TX_ID
+type Groups<'a> = HashMap<u64, Group<'a>>;
+
+// Записи файла, сгруппированные по TX_ID, и TX_ID, встречающиеся несколько раз, в порядке появления
+fn group_records(records: &[TransactionRecord]) -> (Groups<'_>, Vec<Duplicate>) {
+    let mut groups = Groups::new();
+    let mut order = Vec::new();
+    for (position, record) in (1..).zip(records) {
+        let group = groups.entry(record.tx_id).or_default();
+        if group.is_empty() {
+            order.push(record.tx_id);
+        }
+        group.push((position, record));
+    }
+
+    let duplicates = order
+        .into_iter()
+        .filter_map(|tx_id| {
+            let count = groups[&tx_id].len();
+            (count > 1).then_some(Duplicate { tx_id, count })
+        })
+        .collect();
+    (groups, duplicates)
+}
+
+// Группирует записи файла и оставляет в группах записи, выбранные политикой
+fn prepare_records<'a>(
+    records: &'a [TransactionRecord],
+    policy: DuplicatePolicy,
+    path: &Path,
+) -> Result<(Groups<'a>, Vec<Duplicate>)> {
+    let (mut groups, duplicates) = group_records(records);
+    if policy == DuplicatePolicy::Error && !duplicates.is_empty() {
+        let tx_ids: Vec<String> = duplicates.iter().map(|d| d.tx_id.to_string()).collect();
+        bail!(
+            "File '{}' contains duplicate TX_ID values: {}; choose how to compare them with '--duplicates'",
             path.display(),
-            arg_name
-        )
-    })?;
-
-    let records = match format {
-        FileFormat::Csv => {
-            YPBankCsvRecords::from_read(&mut file)
-                .with_context(|| {
-                    format!(
-                    "Failed to parse as csv data from file '{}' (value of '--format1' argument)",
-                    path.display()
-                )
-                })?
-                .records
+            tx_ids.join(", ")
+        );
+    }
+
+    for group in groups.values_mut() {
+        match policy {
+            DuplicatePolicy::First => group.truncate(1),
+            DuplicatePolicy::Last => {
+                group.drain(..group.len() - 1);
+            }
+            DuplicatePolicy::All | DuplicatePolicy::Error => {}
+        }
+    }
+    Ok((groups, duplicates))
+}
+
+// Сравнивает записи с одним TX_ID как мультимножества: сначала взаимно исключаются совпадающие записи,
+// затем оставшиеся записи двух файлов по порядку составляют пары. Возвращает записи первого
+// и второго файла, оставшиеся без пары
+fn compare_groups<'a>(
+    group1: &Group<'a>,
+    group2: &Group<'a>,
+    fields: &[Field],
+    entries: &mut Vec<Entry>,
+) -> (Group<'a>, Group<'a>) {
+    // Ещё не исключённые записи второго файла по значениям сравниваемых полей, в порядке файла
+    let mut by_key: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (i, (_, rec2)) in group2.iter().enumerate() {
+        by_key
+            .entry(FieldChange::key(rec2, fields))
+            .or_default()
+            .push_back(i);
+    }
+    let mut matched2 = vec![false; group2.len()];
+    let mut unmatched1 = Group::new();
+    for &(position, rec1) in group1 {
+        let matched = by_key
+            .get_mut(&FieldChange::key(rec1, fields))
+            .and_then(VecDeque::pop_front);
+        match matched {
+            Some(i) => matched2[i] = true,
+            None => unmatched1.push((position, rec1)),
         }
-        FileFormat::Txt => {
-            YPBankTxtRecords::from_read(&mut file)
-                .with_context(|| {
-                    format!(
-                    "Failed to parse as txt data from file '{}' (value of '--format1' argument)",
-                    path.display()
-                )
-                })?
-                .records
+    }
+    let mut unmatched2: Group<'a> = group2
+        .iter()
+        .zip(&matched2)
+        .filter(|(_, matched)| !**matched)
+        .map(|(record, _)| *record)
+        .collect();
+
+    let paired = unmatched1.len().min(unmatched2.len());
+    for ((_, rec1), (_, rec2)) in unmatched1.drain(..paired).zip(unmatched2.drain(..paired)) {
+        // Ключи записей различаются, поэтому различающиеся поля есть всегда
+        entries.extend(Entry::mismatch(rec1, rec2, fields));
+    }
+    (unmatched1, unmatched2)
+}
+
+// Номера записей (с 1) в файле по TX_ID; для повторяющегося TX_ID — номер записи,
+// выбранной политикой (для `all` — первой)
+fn record_positions(records: &[TransactionRecord], policy: DuplicatePolicy) -> HashMap<u64, usize> {
+    let mut positions = HashMap::new();
+    for (position, record) in (1..).zip(records) {
+        if policy == DuplicatePolicy::Last {
+            positions.insert(record.tx_id, position);
+        } else {
+            positions.entry(record.tx_id).or_insert(position);
+        }
+    }
+    positions
+}
+
+// Отмечает элементы наибольшей возрастающей подпоследовательности seq
+fn longest_increasing(seq: &[usize]) -> Vec<bool> {
+    // tails[k] — индекс последнего элемента лучшей подпоследовательности длины k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![usize::MAX; seq.len()];
+    for (i, value) in seq.iter().enumerate() {
+        let k = tails.partition_point(|&j| seq[j] < *value);
+        if k > 0 {
+            prev[i] = tails[k - 1];
         }
-        FileFormat::Binary => {
-            YPBankBinRecords::from_read(&mut file)
-                .with_context(|| {
-                    format!(
-                    "Failed to parse as binary data from file '{}' (value of '--format1' argument)",
-                    path.display()
-                )
-                })?
-                .records
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
         }
-    };
-    Ok(records)
+    }
+
+    let mut in_order = vec![false; seq.len()];
+    let mut i = tails.last().copied().unwrap_or(usize::MAX);
+    while i != usize::MAX {
+        in_order[i] = true;
+        i = prev[i];
+    }
+    in_order
 }
 
-fn compare_records(
+// Записи, общие для обоих файлов, но стоящие в другой последовательности.
+// Наибольшая часть общих записей, идущих в одном порядке, считается неподвижной, остальные — перемещёнными
+fn moved_records(
     records1: &[TransactionRecord],
     records2: &[TransactionRecord],
-    file1: &Path,
-    file2: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let mut map1: HashMap<u64, &TransactionRecord> = HashMap::new();
-    for record in records1 {
-        map1.insert(record.tx_id, record);
-    }
+    policy: DuplicatePolicy,
+) -> Vec<Entry> {
+    let positions1 = record_positions(records1, policy);
+    let positions2 = record_positions(records2, policy);
 
-    let mut map2: HashMap<u64, &TransactionRecord> = HashMap::new();
-    for record in records2 {
-        map2.insert(record.tx_id, record);
-    }
+    let mut common: Vec<(u64, usize, usize)> = positions1
+        .iter()
+        .filter_map(|(tx_id, &left)| positions2.get(tx_id).map(|&right| (*tx_id, left, right)))
+        .collect();
+    common.sort_unstable_by_key(|&(_, left, _)| left);
 
-    let mut has_diff = false;
+    let rights: Vec<usize> = common.iter().map(|&(_, _, right)| right).collect();
+    common
+        .into_iter()
+        .zip(longest_increasing(&rights))
+        .filter(|(_, in_order)| !in_order)
+        .map(|((tx_id, left_position, right_position), _)| Entry::Moved {
+            tx_id,
+            left_position,
+            right_position,
+        })
+        .collect()
+}
+
+fn compare_records<'a>(
+    records1: &[TransactionRecord],
+    records2: &[TransactionRecord],
+    file1: &'a Path,
+    file2: &'a Path,
+    options: &CompareOptions,
+) -> Result<Report<'a>> {
+    let (map1, duplicates1) = prepare_records(records1, options.duplicates, file1)?;
+    let (map2, duplicates2) = prepare_records(records2, options.duplicates, file2)?;
+    let fields = &options.fields;
+
+    // Номер отсутствующей записи выводится, только если её TX_ID повторяется в файле,
+    // иначе запись однозначно определяется по TX_ID
+    let duplicated1: HashSet<u64> = duplicates1.iter().map(|d| d.tx_id).collect();
+    let duplicated2: HashSet<u64> = duplicates2.iter().map(|d| d.tx_id).collect();
+    let missing_right = |tx_id: u64, group: &Group| -> Vec<Entry> {
+        group
+            .iter()
+            .map(|&(position, _)| Entry::MissingRight {
+                tx_id,
+                position: duplicated1.contains(&tx_id).then_some(position),
+            })
+            .collect()
+    };
+    let missing_left = |tx_id: u64, group: &Group| -> Vec<Entry> {
+        group
+            .iter()
+            .map(|&(position, _)| Entry::MissingLeft {
+                tx_id,
+                position: duplicated2.contains(&tx_id).then_some(position),
+            })
+            .collect()
+    };
+
+    let mut entries = Vec::new();
     // Проверяем записи из первого файла на наличие во втором
-    for (tx_id, rec1) in &map1 {
+    for (tx_id, group1) in &map1 {
         match map2.get(tx_id) {
-            Some(rec2) => {
-                if rec1 != rec2 {
-                    println!("Transaction {} differs:", tx_id);
-                    println!("  In {}: {}", file1.display(), rec1);
-                    println!("  In {}: {}", file2.display(), rec2);
-                    has_diff = true;
-                }
-            }
-            None => {
-                println!(
-                    "Transaction {} present in {} but missing in {}",
-                    tx_id,
-                    file1.display(),
-                    file2.display()
-                );
-                has_diff = true;
+            Some(group2) => {
+                let (unpaired1, unpaired2) = compare_groups(group1, group2, fields, &mut entries);
+                entries.extend(missing_right(*tx_id, &unpaired1));
+                entries.extend(missing_left(*tx_id, &unpaired2));
             }
+            None => entries.extend(missing_right(*tx_id, group1)),
         }
     }
 
     // Проверяем записи из второго файла на наличие в первом
-    for tx_id in map2.keys() {
+    for (tx_id, group2) in &map2 {
         if !map1.contains_key(tx_id) {
-            println!(
-                "Transaction {} present in {} but missing in {}",
-                tx_id,
-                file2.display(),
-                file1.display()
-            );
-            has_diff = true;
+            entries.extend(missing_left(*tx_id, group2));
         }
     }
 
-    if !has_diff {
-        println!("The transaction records are identical.");
+    if options.check_order {
+        entries.extend(moved_records(records1, records2, options.duplicates));
     }
 
-    Ok(())
-}
+    // Различия по одному TX_ID уже упорядочены, поэтому устойчивая сортировка делает вывод детерминированным
+    match options.sort_by {
+        SortKey::TxId => entries.sort_by_key(Entry::tx_id),
+        SortKey::Timestamp => {
+            // Время записи по первому файлу, а для отсутствующих в нём — по второму
+            let timestamp = |tx_id: u64| {
+                map1.get(&tx_id)
+                    .or_else(|| map2.get(&tx_id))
+                    .map_or(0, |group| group[0].1.timestamp())
+            };
+            entries.sort_by_cached_key(|entry| (timestamp(entry.tx_id()), entry.tx_id()));
+        }
+    }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+    Ok(Report {
+        left: file1,
+        right: file2,
+        duplicates: Duplicates {
+            left: duplicates1,
+            right: duplicates2,
+        },
+        entries,
+    })
+}
 
+// Сравнивает файлы и выводит отчёт; возвращает true, если записи совпадают
+fn run(cli: &Cli) -> Result<bool> {
     let records1 = read_records(&cli.file1, cli.format1, "file1")?;
     let records2 = read_records(&cli.file2, cli.format2, "file2")?;
 
-    compare_records(&records1, &records2, &cli.file1, &cli.file2)?;
+    let options = cli.compare_options();
+    let report = compare_records(&records1, &records2, &cli.file1, &cli.file2, &options)?;
+    if !cli.quiet {
+        let out = stdout().lock();
+        if cli.summary {
+            report.summary().write(cli.report_format, out)
+        } else {
+            report.write(cli.report_format, out)
+        }
+        .context("Failed to write comparison report")?;
+    }
+
+    Ok(report.is_identical())
+}
+
+// Коды завершения как у diff(1): 0 — записи совпадают, 1 — есть различия, 2 — ошибка
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::{Status, TxType};
+
+    fn record(tx_id: u64, amount: i64, timestamp: u64) -> TransactionRecord {
+        TransactionRecord::builder()
+            .tx_id(tx_id)
+            .tx_type(TxType::DEPOSIT)
+            .to_user_id(10)
+            .amount(amount)
+            .timestamp(timestamp)
+            .status(Status::SUCCESS)
+            .description(format!("Record {}", tx_id))
+            .build()
+            .unwrap()
+    }
+
+    fn parse(args: &[&str]) -> std::result::Result<Cli, clap::Error> {
+        let required = ["cli-comparer", "--file1", "left", "--file2", "right"];
+        Cli::try_parse_from(required.iter().chain(args))
+    }
+
+    fn compare(
+        records1: &[TransactionRecord],
+        records2: &[TransactionRecord],
+        args: &[&str],
+    ) -> Result<Report<'static>> {
+        let options = parse(args).unwrap().compare_options();
+        compare_records(
+            records1,
+            records2,
+            Path::new("left"),
+            Path::new("right"),
+            &options,
+        )
+    }
+
+    // Вид и TX_ID каждого различия в порядке отчёта
+    fn kinds(report: &Report) -> Vec<(&'static str, u64)> {
+        report
+            .entries
+            .iter()
+            .map(|entry| (entry.kind(), entry.tx_id()))
+            .collect()
+    }
+
+    #[test]
+    fn test_field_selection() {
+        assert_eq!(parse(&[]).unwrap().compare_options().fields, Field::ALL);
+        assert_eq!(
+            parse(&["--ignore-field", "amount", "--ignore-field", "description"])
+                .unwrap()
+                .compare_options()
+                .fields,
+            [
+                Field::TxType,
+                Field::FromUserId,
+                Field::ToUserId,
+                Field::Timestamp,
+                Field::Status
+            ]
+        );
+        // Поля сравниваются в порядке вывода, а не в порядке аргумента
+        assert_eq!(
+            parse(&["--only-fields", "timestamp,amount"])
+                .unwrap()
+                .compare_options()
+                .fields,
+            [Field::Amount, Field::Timestamp]
+        );
+        assert!(parse(&["--only-fields", "amount", "--ignore-field", "status"]).is_err());
+        // Записи сопоставляются по TX_ID, поэтому выбрать его для сравнения нельзя
+        assert!(parse(&["--ignore-field", "tx_id"]).is_err());
+        assert!(parse(&["--only-fields", "amount,tx_id"]).is_err());
+
+        let left = [record(1, 100, 1000)];
+        let right = [record(1, 200, 1000)];
+        assert!(!compare(&left, &right, &[]).unwrap().is_identical());
+        assert!(compare(&left, &right, &["--ignore-field", "amount"])
+            .unwrap()
+            .is_identical());
+        assert!(compare(&left, &right, &["--only-fields", "timestamp"])
+            .unwrap()
+            .is_identical());
+    }
+
+    #[test]
+    fn test_missing_and_mismatch() {
+        let left = [
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+            record(3, 300, 3000),
+        ];
+        let right = [
+            record(4, 400, 4000),
+            record(2, 250, 2000),
+            record(1, 100, 1000),
+        ];
+        let report = compare(&left, &right, &[]).unwrap();
+        assert_eq!(
+            kinds(&report),
+            [("mismatch", 2), ("missing_right", 3), ("missing_left", 4)]
+        );
+        let Entry::Mismatch { fields, .. } = &report.entries[0] else {
+            panic!("expected mismatch");
+        };
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, "amount");
+        assert_eq!(
+            (&fields[0].left, &fields[0].right),
+            (&200.into(), &250.into())
+        );
+
+        assert!(compare(&left, &left, &[]).unwrap().is_identical());
+    }
+
+    #[test]
+    fn test_bin_against_csv() {
+        // В BIN-файле суммы WITHDRAWAL отрицательные, а в CSV записывается их величина
+        let bin_path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../test_files/records_example.bin");
+        let from_bin = read_records(&bin_path, None, "file1").unwrap();
+        assert!(from_bin
+            .iter()
+            .any(|record| record.tx_type() == TxType::WITHDRAWAL));
+
+        let mut csv = Vec::new();
+        let mut writer = parser::record_writer(Format::Csv, &mut csv);
+        for record in &from_bin {
+            writer.write_record(record).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        let from_csv = parser::record_reader(Format::Csv, csv.as_slice())
+            .collect::<parser::ParseResult<Vec<_>>>()
+            .unwrap();
+
+        assert!(compare(&from_bin, &from_csv, &["--check-order"])
+            .unwrap()
+            .is_identical());
+
+        // Смена типа видна в tx_type, а не как смена знака суммы
+        let deposit = record(1, 300, 1000);
+        let withdrawal = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::WITHDRAWAL)
+            .from_user_id(10)
+            .amount(-300)
+            .timestamp(1000)
+            .status(Status::SUCCESS)
+            .description("Record 1")
+            .build()
+            .unwrap();
+        let report = compare(&[deposit], &[withdrawal], &[]).unwrap();
+        let Entry::Mismatch { fields, .. } = &report.entries[0] else {
+            panic!("expected mismatch");
+        };
+        let names: Vec<&str> = fields.iter().map(|change| change.field).collect();
+        assert_eq!(names, ["tx_type", "from_user_id", "to_user_id"]);
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let left = [
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+            record(1, 150, 1000),
+        ];
+        let right = [record(2, 200, 2000), record(1, 150, 1000)];
+
+        let err = compare(&left, &right, &[]).unwrap_err();
+        assert!(err.to_string().contains("duplicate TX_ID values: 1"));
 
-    Ok(())
+        let report = compare(&left, &right, &["--duplicates", "first"]).unwrap();
+        assert_eq!(kinds(&report), [("mismatch", 1)]);
+        assert_eq!(report.duplicates.left.len(), 1);
+        assert_eq!(
+            (
+                report.duplicates.left[0].tx_id,
+                report.duplicates.left[0].count
+            ),
+            (1, 2)
+        );
+        assert!(report.duplicates.right.is_empty());
+
+        let report = compare(&left, &right, &["--duplicates", "last"]).unwrap();
+        assert!(report.is_identical());
+
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        assert_eq!(kinds(&report), [("missing_right", 1)]);
+        assert!(matches!(
+            report.entries[0],
+            Entry::MissingRight {
+                position: Some(1),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_pairing() {
+        // Совпадающие записи исключаются, даже если стоят в группе не первыми
+        let left = [record(1, 100, 1000), record(1, 200, 2000)];
+        let right = [record(1, 200, 2000), record(1, 100, 1001)];
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        assert_eq!(kinds(&report), [("mismatch", 1)]);
+        let Entry::Mismatch { fields, .. } = &report.entries[0] else {
+            panic!("expected mismatch");
+        };
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].field, "timestamp");
+
+        // Оставшиеся записи сопоставляются по порядку
+        let left = [
+            record(1, 100, 1000),
+            record(1, 500, 5000),
+            record(2, 200, 2000),
+        ];
+        let right = [record(1, 500, 5001), record(2, 200, 2000)];
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        assert_eq!(kinds(&report), [("mismatch", 1), ("missing_right", 1)]);
+        let Entry::Mismatch { fields, .. } = &report.entries[0] else {
+            panic!("expected mismatch");
+        };
+        assert_eq!(fields.len(), 2);
+        assert!(matches!(
+            report.entries[1],
+            Entry::MissingRight {
+                position: Some(2),
+                ..
+            }
+        ));
+
+        // Номер записи указывается только для повторяющегося в файле TX_ID
+        let right = [
+            record(3, 300, 3000),
+            record(1, 100, 1000),
+            record(3, 300, 3000),
+        ];
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        let positions: Vec<(&str, u64, Option<usize>)> = report
+            .entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::MissingLeft { tx_id, position } => ("missing_left", *tx_id, *position),
+                Entry::MissingRight { tx_id, position } => ("missing_right", *tx_id, *position),
+                _ => panic!("unexpected {:?}", entry),
+            })
+            .collect();
+        assert_eq!(
+            positions,
+            [
+                ("missing_right", 1, Some(2)),
+                ("missing_right", 2, None),
+                ("missing_left", 3, Some(1)),
+                ("missing_left", 3, Some(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_duplicate_group() {
+        // Группа из десятков тысяч записей с одним TX_ID сравнивается за линейное время
+        let count = 50_000;
+        let mut left: Vec<_> = (0..count).map(|i| record(1, i, 1000)).collect();
+        let mut right: Vec<_> = (0..count).rev().map(|i| record(1, i, 1000)).collect();
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        assert!(report.is_identical());
+
+        left.push(record(1, 7, 2000));
+        right.push(record(1, count + 1, 1000));
+        right.push(record(1, count + 2, 1000));
+        let report = compare(&left, &right, &["--duplicates", "all"]).unwrap();
+        assert_eq!(kinds(&report), [("mismatch", 1), ("missing_left", 1)]);
+        assert!(matches!(
+            report.entries[1],
+            Entry::MissingLeft {
+                position: Some(position),
+                ..
+            } if position == count as usize + 2
+        ));
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let left = [record(1, 100, 3000), record(2, 200, 1000)];
+        let right = [record(3, 300, 2000)];
+        let report = compare(&left, &right, &[]).unwrap();
+        assert_eq!(
+            kinds(&report),
+            [
+                ("missing_right", 1),
+                ("missing_right", 2),
+                ("missing_left", 3)
+            ]
+        );
+        let report = compare(&left, &right, &["--sort-by", "timestamp"]).unwrap();
+        assert_eq!(
+            kinds(&report),
+            [
+                ("missing_right", 2),
+                ("missing_left", 3),
+                ("missing_right", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_order() {
+        let left = [
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+            record(3, 300, 3000),
+        ];
+        let right = [
+            record(3, 300, 3000),
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+        ];
+        assert!(compare(&left, &right, &[]).unwrap().is_identical());
+
+        let report = compare(&left, &right, &["--check-order"]).unwrap();
+        assert_eq!(kinds(&report), [("moved", 3)]);
+        assert!(matches!(
+            report.entries[0],
+            Entry::Moved {
+                left_position: 3,
+                right_position: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_record_positions() {
+        let records = [
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+            record(1, 150, 1000),
+        ];
+        let first = record_positions(&records, DuplicatePolicy::First);
+        assert_eq!((first[&1], first[&2]), (1, 2));
+        let last = record_positions(&records, DuplicatePolicy::Last);
+        assert_eq!((last[&1], last[&2]), (3, 2));
+    }
+
+    #[test]
+    fn test_longest_increasing() {
+        assert_eq!(longest_increasing(&[]), Vec::<bool>::new());
+        assert_eq!(longest_increasing(&[1, 2, 3]), [true, true, true]);
+        assert_eq!(longest_increasing(&[2, 3, 1]), [true, true, false]);
+        assert_eq!(longest_increasing(&[1, 3, 2, 4]), [true, false, true, true]);
+        assert_eq!(
+            longest_increasing(&[4, 3, 2, 1]),
+            [false, false, false, true]
+        );
+        assert_eq!(
+            longest_increasing(&[3, 1, 4, 2, 5]),
+            [false, true, false, true, true]
+        );
+    }
+
+    // TX_ID и позиции перемещённых записей в порядке отчёта
+    fn moved(report: &Report) -> Vec<(u64, usize, usize)> {
+        report
+            .entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Entry::Moved {
+                    tx_id,
+                    left_position,
+                    right_position,
+                } => Some((*tx_id, *left_position, *right_position)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_moved_records() {
+        let records: Vec<TransactionRecord> = (1..=4)
+            .map(|tx_id| record(tx_id, 100 * tx_id as i64, 1000 * tx_id))
+            .collect();
+        let reordered = |order: &[usize]| -> Vec<TransactionRecord> {
+            order.iter().map(|&i| records[i - 1].clone()).collect()
+        };
+
+        // Перестановка двух соседних записей — одна перемещённая запись
+        let report = compare(&records, &reordered(&[1, 3, 2, 4]), &["--check-order"]).unwrap();
+        assert_eq!(moved(&report), [(2, 2, 3)]);
+        assert_eq!(report.entries.len(), 1);
+
+        // Обратный порядок — неподвижной остаётся одна запись
+        let report = compare(&records, &reordered(&[4, 3, 2, 1]), &["--check-order"]).unwrap();
+        assert_eq!(moved(&report), [(1, 1, 4), (2, 2, 3), (3, 3, 2)]);
+
+        // Записи только из одного файла не перемещены, но учитываются в номерах записей
+        let mut right = vec![record(5, 500, 5000)];
+        right.extend(reordered(&[3, 1, 2]));
+        let report = compare(&records, &right, &["--check-order"]).unwrap();
+        assert_eq!(
+            kinds(&report),
+            [("moved", 3), ("missing_right", 4), ("missing_left", 5)]
+        );
+        assert_eq!(moved(&report), [(3, 3, 2)]);
+    }
+
+    #[test]
+    fn test_moved_duplicates() {
+        let left = [
+            record(1, 100, 1000),
+            record(2, 200, 2000),
+            record(1, 100, 1000),
+        ];
+        let right = [record(2, 200, 2000), record(1, 100, 1000)];
+        let check = |policy: &str| {
+            compare(&left, &right, &["--check-order", "--duplicates", policy]).unwrap()
+        };
+
+        // Для first и all учитывается первая запись с TX_ID 1, для last — последняя
+        assert_eq!(moved(&check("first")), [(1, 1, 2)]);
+        assert!(check("last").is_identical());
+        let report = check("all");
+        assert_eq!(kinds(&report), [("missing_right", 1), ("moved", 1)]);
+        assert_eq!(moved(&report), [(1, 1, 2)]);
+        assert!(matches!(
+            report.entries[0],
+            Entry::MissingRight {
+                position: Some(3),
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,568 @@
+use parser::TransactionRecord;
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::Path;
+
+/// Формат отчёта о различиях
+#[derive(Copy, Clone, PartialEq, Eq, clap::ValueEnum, Debug)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Поле записи, участвующее в сравнении.
+///
+/// `TX_ID` в списке нет: записи сопоставляются по нему, поэтому у пары записей он всегда совпадает.
+#[derive(Copy, Clone, PartialEq, Eq, clap::ValueEnum, Debug)]
+#[value(rename_all = "snake_case")]
+pub enum Field {
+    TxType,
+    FromUserId,
+    ToUserId,
+    Amount,
+    Timestamp,
+    Status,
+    Description,
+}
+
+impl Field {
+    /// Все поля в порядке вывода
+    pub const ALL: [Field; 7] = [
+        Field::TxType,
+        Field::FromUserId,
+        Field::ToUserId,
+        Field::Amount,
+        Field::Timestamp,
+        Field::Status,
+        Field::Description,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::TxType => "tx_type",
+            Field::FromUserId => "from_user_id",
+            Field::ToUserId => "to_user_id",
+            Field::Amount => "amount",
+            Field::Timestamp => "timestamp",
+            Field::Status => "status",
+            Field::Description => "description",
+        }
+    }
+
+    fn value(self, record: &TransactionRecord) -> Value {
+        match self {
+            Field::TxType => record.tx_type().to_string().into(),
+            Field::FromUserId => record.from_user_id().into(),
+            Field::ToUserId => record.to_user_id().into(),
+            // Величина суммы, как в CSV и TXT; знак определяется типом и сравнивается в поле tx_type
+            Field::Amount => record.amount_abs().into(),
+            Field::Timestamp => record.timestamp().into(),
+            Field::Status => record.status().to_string().into(),
+            Field::Description => record.description().into(),
+        }
+    }
+}
+
+/// Изменённое поле записи: значение в первом (`left`) и во втором (`right`) файле
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub left: Value,
+    pub right: Value,
+}
+
+impl FieldChange {
+    /// Поля fields, различающиеся у двух записей, в порядке fields
+    /// Значения полей `fields` записи одной строкой: у записей, не различающихся по этим полям,
+    /// ключи совпадают
+    pub fn key(record: &TransactionRecord, fields: &[Field]) -> String {
+        let values = fields.iter().map(|field| field.value(record)).collect();
+        Value::Array(values).to_string()
+    }
+
+    pub fn between(
+        left: &TransactionRecord,
+        right: &TransactionRecord,
+        fields: &[Field],
+    ) -> Vec<Self> {
+        fields
+            .iter()
+            .filter_map(|field| {
+                let (left, right) = (field.value(left), field.value(right));
+                (left != right).then_some(FieldChange {
+                    field: field.name(),
+                    left,
+                    right,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Различие между файлами по одному `TX_ID`
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+    /// Запись есть только во втором файле; `position` — её номер (с 1), если `TX_ID` там повторяется
+    MissingLeft {
+        tx_id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        position: Option<usize>,
+    },
+    /// Запись есть только в первом файле; `position` — её номер (с 1), если `TX_ID` там повторяется
+    MissingRight {
+        tx_id: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        position: Option<usize>,
+    },
+    /// Запись есть в обоих файлах, но поля различаются
+    Mismatch {
+        tx_id: u64,
+        fields: Vec<FieldChange>,
+    },
+    /// Запись есть в обоих файлах, но стоит в другой последовательности; позиции — номера записей с 1
+    Moved {
+        tx_id: u64,
+        left_position: usize,
+        right_position: usize,
+    },
+}
+
+impl Entry {
+    /// Сравнивает поля fields двух записей с одинаковым `TX_ID`; `None`, если они совпадают
+    pub fn mismatch(
+        left: &TransactionRecord,
+        right: &TransactionRecord,
+        fields: &[Field],
+    ) -> Option<Self> {
+        let fields = FieldChange::between(left, right, fields);
+        if fields.is_empty() {
+            return None;
+        }
+        Some(Entry::Mismatch {
+            tx_id: left.tx_id(),
+            fields,
+        })
+    }
+
+    pub fn tx_id(&self) -> u64 {
+        match self {
+            Entry::MissingLeft { tx_id, .. }
+            | Entry::MissingRight { tx_id, .. }
+            | Entry::Mismatch { tx_id, .. }
+            | Entry::Moved { tx_id, .. } => *tx_id,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Entry::MissingLeft { .. } => "missing_left",
+            Entry::MissingRight { .. } => "missing_right",
+            Entry::Mismatch { .. } => "mismatch",
+            Entry::Moved { .. } => "moved",
+        }
+    }
+}
+
+/// `TX_ID`, встречающийся в одном файле несколько раз
+#[derive(Debug, Serialize)]
+pub struct Duplicate {
+    pub tx_id: u64,
+    pub count: usize,
+}
+
+/// Повторяющиеся `TX_ID` первого (`left`) и второго (`right`) файла
+#[derive(Debug, Default, Serialize)]
+pub struct Duplicates {
+    pub left: Vec<Duplicate>,
+    pub right: Vec<Duplicate>,
+}
+
+/// Число различий каждого вида
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub missing_left: usize,
+    pub missing_right: usize,
+    pub mismatch: usize,
+    pub moved: usize,
+    pub duplicates_left: usize,
+    pub duplicates_right: usize,
+}
+
+impl Summary {
+    pub fn write<W: Write>(&self, format: ReportFormat, mut w: W) -> anyhow::Result<()> {
+        match format {
+            ReportFormat::Text => {
+                writeln!(w, "Missing in first file: {}", self.missing_left)?;
+                writeln!(w, "Missing in second file: {}", self.missing_right)?;
+                writeln!(w, "Differing: {}", self.mismatch)?;
+                writeln!(w, "Out of order: {}", self.moved)?;
+                writeln!(w, "Duplicate in first file: {}", self.duplicates_left)?;
+                writeln!(w, "Duplicate in second file: {}", self.duplicates_right)?;
+            }
+            ReportFormat::Json => {
+                serde_json::to_writer_pretty(&mut w, self)?;
+                writeln!(w)?;
+            }
+            ReportFormat::Csv => {
+                let mut csv = csv::Writer::from_writer(w);
+                csv.serialize(self)?;
+                csv.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Отчёт о сравнении двух файлов
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    pub left: &'a Path,
+    pub right: &'a Path,
+    pub duplicates: Duplicates,
+    pub entries: Vec<Entry>,
+}
+
+impl Report<'_> {
+    pub fn is_identical(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut summary = Summary {
+            duplicates_left: self.duplicates.left.len(),
+            duplicates_right: self.duplicates.right.len(),
+            ..Summary::default()
+        };
+        for entry in &self.entries {
+            match entry {
+                Entry::MissingLeft { .. } => summary.missing_left += 1,
+                Entry::MissingRight { .. } => summary.missing_right += 1,
+                Entry::Mismatch { .. } => summary.mismatch += 1,
+                Entry::Moved { .. } => summary.moved += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn write<W: Write>(&self, format: ReportFormat, w: W) -> anyhow::Result<()> {
+        match format {
+            ReportFormat::Text => self.write_text(w)?,
+            ReportFormat::Json => self.write_json(w)?,
+            ReportFormat::Csv => self.write_csv(w)?,
+        }
+        Ok(())
+    }
+
+    fn write_text<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        for (path, duplicates) in [
+            (self.left, &self.duplicates.left),
+            (self.right, &self.duplicates.right),
+        ] {
+            for duplicate in duplicates {
+                writeln!(
+                    w,
+                    "Transaction {} occurs {} times in {}",
+                    duplicate.tx_id,
+                    duplicate.count,
+                    path.display()
+                )?;
+            }
+        }
+
+        if self.is_identical() {
+            return writeln!(w, "The transaction records are identical.");
+        }
+        for entry in &self.entries {
+            match entry {
+                Entry::MissingLeft { tx_id, position } => writeln!(
+                    w,
+                    "Transaction {} present in {}{} but missing in {}",
+                    tx_id,
+                    self.right.display(),
+                    record_text(*position),
+                    self.left.display()
+                )?,
+                Entry::MissingRight { tx_id, position } => writeln!(
+                    w,
+                    "Transaction {} present in {}{} but missing in {}",
+                    tx_id,
+                    self.left.display(),
+                    record_text(*position),
+                    self.right.display()
+                )?,
+                Entry::Mismatch { tx_id, fields } => {
+                    writeln!(w, "Transaction {} differs:", tx_id)?;
+                    for change in fields {
+                        writeln!(
+                            w,
+                            "  {}: {} → {}",
+                            change.field,
+                            value_text(&change.left),
+                            value_text(&change.right)
+                        )?;
+                    }
+                }
+                Entry::Moved {
+                    tx_id,
+                    left_position,
+                    right_position,
+                } => writeln!(
+                    w,
+                    "Transaction {} is out of order: record {} in {} but record {} in {}",
+                    tx_id,
+                    left_position,
+                    self.left.display(),
+                    right_position,
+                    self.right.display()
+                )?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_json<W: Write>(&self, mut w: W) -> anyhow::Result<()> {
+        serde_json::to_writer_pretty(&mut w, self)?;
+        writeln!(w)?;
+        Ok(())
+    }
+
+    // Одна строка на каждое изменённое поле; у отсутствующих записей поля пустые, кроме номера записи
+    // с повторяющимся TX_ID в столбце файла, где она есть; у повторяющихся TX_ID там же указано число повторов
+    fn write_csv<W: Write>(&self, w: W) -> anyhow::Result<()> {
+        let mut csv = csv::Writer::from_writer(w);
+        csv.write_record(["kind", "tx_id", "field", "left", "right"])?;
+        for duplicate in &self.duplicates.left {
+            let count = duplicate.count.to_string();
+            csv.write_record(["duplicate", &duplicate.tx_id.to_string(), "", &count, ""])?;
+        }
+        for duplicate in &self.duplicates.right {
+            let count = duplicate.count.to_string();
+            csv.write_record(["duplicate", &duplicate.tx_id.to_string(), "", "", &count])?;
+        }
+        for entry in &self.entries {
+            let tx_id = entry.tx_id().to_string();
+            match entry {
+                Entry::Mismatch { fields, .. } => {
+                    for change in fields {
+                        csv.write_record([
+                            entry.kind(),
+                            &tx_id,
+                            change.field,
+                            &value_text(&change.left),
+                            &value_text(&change.right),
+                        ])?;
+                    }
+                }
+                Entry::Moved {
+                    left_position,
+                    right_position,
+                    ..
+                } => csv.write_record([
+                    entry.kind(),
+                    &tx_id,
+                    "",
+                    &left_position.to_string(),
+                    &right_position.to_string(),
+                ])?,
+                Entry::MissingLeft { position, .. } => {
+                    csv.write_record([entry.kind(), &tx_id, "", "", &position_text(*position)])?
+                }
+                Entry::MissingRight { position, .. } => {
+                    csv.write_record([entry.kind(), &tx_id, "", &position_text(*position), ""])?
+                }
+            }
+        }
+        csv.flush()?;
+        Ok(())
+    }
+}
+
+// Номер записи с повторяющимся TX_ID для текстового отчёта
+fn record_text(position: Option<usize>) -> String {
+    position.map_or_else(String::new, |position| format!(" (record {})", position))
+}
+
+fn position_text(position: Option<usize>) -> String {
+    position.map_or_else(String::new, |position| position.to_string())
+}
+
+// Значение поля без кавычек JSON
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parser::{Status, TxType};
+
+    fn record(tx_id: u64, amount: i64, description: &str) -> TransactionRecord {
+        TransactionRecord::builder()
+            .tx_id(tx_id)
+            .tx_type(TxType::DEPOSIT)
+            .to_user_id(10)
+            .amount(amount)
+            .timestamp(1000)
+            .status(Status::SUCCESS)
+            .description(description)
+            .build()
+            .unwrap()
+    }
+
+    fn sample_report() -> Report<'static> {
+        let mismatch = Entry::mismatch(
+            &record(2, 100, "Rent"),
+            &record(2, 150, "Rent, March"),
+            &Field::ALL,
+        )
+        .unwrap();
+        Report {
+            left: Path::new("left.csv"),
+            right: Path::new("right.csv"),
+            duplicates: Duplicates {
+                left: vec![Duplicate { tx_id: 7, count: 2 }],
+                right: Vec::new(),
+            },
+            entries: vec![
+                Entry::MissingLeft {
+                    tx_id: 1,
+                    position: None,
+                },
+                mismatch,
+                Entry::MissingRight {
+                    tx_id: 3,
+                    position: None,
+                },
+                Entry::MissingRight {
+                    tx_id: 7,
+                    position: Some(5),
+                },
+                Entry::Moved {
+                    tx_id: 4,
+                    left_position: 4,
+                    right_position: 1,
+                },
+            ],
+        }
+    }
+
+    fn written(write: impl FnOnce(&mut Vec<u8>) -> anyhow::Result<()>) -> String {
+        let mut out = Vec::new();
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_mismatch_fields() {
+        let left = record(1, 100, "Salary");
+        assert!(Entry::mismatch(&left, &left, &Field::ALL).is_none());
+
+        let right = record(1, 200, "Bonus");
+        let Some(Entry::Mismatch { tx_id, fields }) = Entry::mismatch(&left, &right, &Field::ALL)
+        else {
+            panic!("expected mismatch");
+        };
+        assert_eq!(tx_id, 1);
+        let names: Vec<&str> = fields.iter().map(|change| change.field).collect();
+        assert_eq!(names, ["amount", "description"]);
+        assert!(Entry::mismatch(&left, &right, &[Field::Timestamp]).is_none());
+    }
+
+    #[test]
+    fn test_write_text() {
+        let text = written(|out| sample_report().write(ReportFormat::Text, out));
+        assert_eq!(
+            text,
+            "\
+Transaction 7 occurs 2 times in left.csv
+Transaction 1 present in right.csv but missing in left.csv
+Transaction 2 differs:
+  amount: 100 → 150
+  description: Rent → Rent, March
+Transaction 3 present in left.csv but missing in right.csv
+Transaction 7 present in left.csv (record 5) but missing in right.csv
+Transaction 4 is out of order: record 4 in left.csv but record 1 in right.csv
+"
+        );
+
+        let identical = Report {
+            duplicates: Duplicates::default(),
+            entries: Vec::new(),
+            ..sample_report()
+        };
+        let text = written(|out| identical.write(ReportFormat::Text, out));
+        assert_eq!(text, "The transaction records are identical.\n");
+    }
+
+    #[test]
+    fn test_write_json() {
+        let json = written(|out| sample_report().write(ReportFormat::Json, out));
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "left": "left.csv",
+                "right": "right.csv",
+                "duplicates": {"left": [{"tx_id": 7, "count": 2}], "right": []},
+                "entries": [
+                    {"kind": "missing_left", "tx_id": 1},
+                    {"kind": "mismatch", "tx_id": 2, "fields": [
+                        {"field": "amount", "left": 100, "right": 150},
+                        {"field": "description", "left": "Rent", "right": "Rent, March"}
+                    ]},
+                    {"kind": "missing_right", "tx_id": 3},
+                    {"kind": "missing_right", "tx_id": 7, "position": 5},
+                    {"kind": "moved", "tx_id": 4, "left_position": 4, "right_position": 1}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let csv = written(|out| sample_report().write(ReportFormat::Csv, out));
+        assert_eq!(
+            csv,
+            "\
+kind,tx_id,field,left,right
+duplicate,7,,2,
+missing_left,1,,,
+mismatch,2,amount,100,150
+mismatch,2,description,Rent,\"Rent, March\"
+missing_right,3,,,
+missing_right,7,,5,
+moved,4,,4,1
+"
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = sample_report().summary();
+        let text = written(|out| summary.write(ReportFormat::Text, out));
+        assert_eq!(
+            text,
+            "\
+Missing in first file: 1
+Missing in second file: 2
+Differing: 1
+Out of order: 1
+Duplicate in first file: 1
+Duplicate in second file: 0
+"
+        );
+
+        let csv = written(|out| summary.write(ReportFormat::Csv, out));
+        assert_eq!(
+            csv,
+            "missing_left,missing_right,mismatch,moved,duplicates_left,duplicates_right\n\
+             1,2,1,1,1,0\n"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// Сравнение вывода и кода завершения cli-comparer с эталонами из tests/golden.
+// Утилита запускается в каталоге tests/data, поэтому пути файлов в отчётах относительные
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cli-comparer"))
+        .current_dir(tests_dir().join("data"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn golden(name: &str) -> String {
+    std::fs::read_to_string(tests_dir().join("golden").join(name)).unwrap()
+}
+
+fn assert_golden(args: &[&str], name: &str) {
+    let output = run(args);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), golden(name));
+}
+
+const DIFFERENT: [&str; 5] = [
+    "--file1",
+    "left.csv",
+    "--file2",
+    "right.csv",
+    "--check-order",
+];
+
+#[test]
+fn test_report_formats() {
+    for (format, name) in [
+        ("text", "report.txt"),
+        ("json", "report.json"),
+        ("csv", "report.csv"),
+    ] {
+        assert_golden(
+            &[&DIFFERENT[..], &["--report-format", format]].concat(),
+            name,
+        );
+    }
+}
+
+#[test]
+fn test_summary_formats() {
+    for (format, name) in [
+        ("text", "summary.txt"),
+        ("json", "summary.json"),
+        ("csv", "summary.csv"),
+    ] {
+        assert_golden(
+            &[&DIFFERENT[..], &["--summary", "--report-format", format]].concat(),
+            name,
+        );
+    }
+}
+
+#[test]
+fn test_duplicates() {
+    let output = run(&["--file1", "duplicates.csv", "--file2", "left.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error: File 'duplicates.csv' contains duplicate TX_ID values: 1;"));
+
+    assert_golden(
+        &[
+            "--file1",
+            "duplicates.csv",
+            "--file2",
+            "left.csv",
+            "--duplicates",
+            "all",
+        ],
+        "duplicates_all.txt",
+    );
+}
+
+#[test]
+fn test_exit_codes() {
+    let output = run(&["--file1", "left.csv", "--file2", "left.csv"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "The transaction records are identical.\n"
+    );
+
+    let output = run(&[&DIFFERENT[..], &["-q"]].concat());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    let output = run(&["--file1", "left.csv", "--file2", "missing.csv", "-q"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Error: Invalid input file in '--file2' argument"));
+
+    // -q и --summary взаимоисключающие; ошибка разбора аргументов тоже завершается кодом 2
+    let output = run(&[&DIFFERENT[..], &["-q", "--summary"]].concat());
+    assert_eq!(output.status.code(), Some(2));
+}
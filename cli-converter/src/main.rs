@@ -1,14 +1,36 @@
 use anyhow::Context;
 use clap::{Parser, ValueEnum};
-use parser::{RecordParser, YPBankBinRecords, YPBankCsvRecords, YPBankTxtRecords};
+use cli_support::FileFormat;
+use parser::{
+    BinVersion, BinWriteOptions, Format, RecordReader, RecordWriter, TransactionRecord,
+    TxtFieldOrder, TxtRecordHeader, TxtWriteOptions, YPBankBinReader, YPBankBinRecords,
+    YPBankBinWriter, YPBankTxtReader, YPBankTxtWriter,
+};
 use std::fs::File;
-use std::io::stdout;
+use std::io::{stdout, BufWriter, Write};
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
-enum FileFormat {
-    Csv,
-    Txt,
-    Binary,
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum TxtHeader {
+    /// `# Record N (Type)` comment before each record
+    Spec,
+    /// No comment lines
+    None,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum TxtOrder {
+    /// Field order of the specification
+    Canonical,
+    /// Field order of each input record; requires txt input
+    Preserve,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum BinContainer {
+    /// Bare sequence of records, as in the original specification
+    V1,
+    /// File header with version, creation time, record count and body checksum
+    V2,
 }
 
 #[derive(Parser)]
@@ -17,59 +39,281 @@ struct Cli {
     #[arg(short, long)]
     input: String,
 
+    /// Input format; detected from the file contents when omitted
     #[arg(short = 'f', long = "input-format", value_enum)]
-    input_format: FileFormat,
+    input_format: Option<FileFormat>,
 
     #[arg(short = 'o', long = "output-format")]
     output_format: FileFormat,
+
+    /// Skip corrupted regions of binary input, resynchronising on the next record MAGIC
+    #[arg(long, conflicts_with = "skip_invalid")]
+    recover: bool,
+
+    /// Skip records that fail to parse and continue; each skipped record is reported to stderr
+    #[arg(long)]
+    skip_invalid: bool,
+
+    /// Write the raw input of skipped records to this file
+    #[arg(long, requires = "skip_invalid")]
+    rejects: Option<String>,
+
+    /// Comment written before each record of txt output
+    #[arg(long, value_enum, default_value_t = TxtHeader::Spec)]
+    txt_header: TxtHeader,
+
+    /// Field order of txt output
+    #[arg(long, value_enum, default_value_t = TxtOrder::Canonical)]
+    txt_field_order: TxtOrder,
+
+    /// Container version of binary output
+    #[arg(long, value_enum, default_value_t = BinContainer::V1)]
+    bin_version: BinContainer,
+
+    /// Append a CRC32 checksum to every record of binary output; requires --bin-version v2
+    #[arg(long)]
+    record_checksums: bool,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+impl Cli {
+    fn bin_options(&self) -> BinWriteOptions {
+        BinWriteOptions {
+            version: match self.bin_version {
+                BinContainer::V1 => BinVersion::V1,
+                BinContainer::V2 => BinVersion::V2,
+            },
+            created_at: None,
+            record_checksums: self.record_checksums,
+        }
+    }
+
+    fn txt_options(&self) -> TxtWriteOptions {
+        TxtWriteOptions {
+            header: match self.txt_header {
+                TxtHeader::Spec => TxtRecordHeader::Spec,
+                TxtHeader::None => TxtRecordHeader::None,
+            },
+            field_order: match self.txt_field_order {
+                TxtOrder::Canonical => TxtFieldOrder::Canonical,
+                TxtOrder::Preserve => TxtFieldOrder::Preserve,
+            },
+        }
+    }
+}
+
+fn copy_records(
+    reader: &mut dyn RecordReader,
+    writer: &mut dyn RecordWriter,
+    input_format: Format,
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    let output_format = Format::from(cli.output_format);
+
+    for record in reader {
+        let record = record.with_context(|| {
+            format!(
+                "Failed to parse as {} data from file '{}'",
+                input_format, cli.input
+            )
+        })?;
+        writer
+            .write_record(&record)
+            .with_context(|| format!("Failed to write output as {} records", output_format))?;
+    }
+    writer
+        .finish()
+        .with_context(|| format!("Failed to write output as {} records", output_format))?;
+
+    Ok(())
+}
 
-    let mut input = File::open(&cli.input).with_context(|| {
+fn copy_valid_records(
+    reader: &mut dyn RecordReader,
+    writer: &mut dyn RecordWriter,
+    mut rejects: Option<&mut dyn Write>,
+    input_format: Format,
+    cli: &Cli,
+) -> anyhow::Result<usize> {
+    let output_format = Format::from(cli.output_format);
+    let mut skipped = 0;
+
+    while let Some(result) = reader.read_record_lenient().with_context(|| {
         format!(
-            "Failed to open input file '{}' specified in --input argument",
-            cli.input
+            "Failed to read {} data from file '{}'",
+            input_format, cli.input
         )
-    })?;
-
-    let records = match cli.input_format {
-        FileFormat::Csv => {
-            YPBankCsvRecords::from_read(&mut input)
-                .with_context(|| format!("Failed to parse as csv data from file '{}'", cli.input))?
-                .records
-        }
-        FileFormat::Txt => {
-            YPBankTxtRecords::from_read(&mut input)
-                .with_context(|| format!("Failed to parse as txt data from file '{}'", cli.input))?
-                .records
+    })? {
+        match result {
+            Ok(record) => writer
+                .write_record(&record)
+                .with_context(|| format!("Failed to write output as {} records", output_format))?,
+            Err(issue) => {
+                skipped += 1;
+                eprintln!("Skipped invalid record: {}", issue.error);
+                if let Some(rejects) = rejects.as_mut() {
+                    rejects
+                        .write_all(&issue.raw)
+                        .context("Failed to write rejected record")?;
+                }
+            }
         }
-        FileFormat::Binary => {
-            YPBankBinRecords::from_read(&mut input)
-                .with_context(|| {
-                    format!("Failed to parse as binary data from file '{}'", cli.input)
-                })?
-                .records
+    }
+    writer
+        .finish()
+        .with_context(|| format!("Failed to write output as {} records", output_format))?;
+    if let Some(rejects) = rejects {
+        rejects.flush().context("Failed to write rejected record")?;
+    }
+
+    Ok(skipped)
+}
+
+// Копирует TXT-записи, сохраняя порядок полей каждой записи
+fn copy_txt_records<W: Write>(
+    reader: &mut YPBankTxtReader<cli_support::Input>,
+    writer: &mut YPBankTxtWriter<W>,
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    while let Some(record) = reader
+        .read_record()
+        .with_context(|| format!("Failed to parse as txt data from file '{}'", cli.input))?
+    {
+        writer
+            .write_record_in_order(&record, reader.field_order())
+            .context("Failed to write output as txt records")?;
+    }
+    writer
+        .finish()
+        .context("Failed to write output as txt records")?;
+
+    Ok(())
+}
+
+// Писатель BIN версии 2 в вывод без Seek: записи накапливаются в памяти и выводятся
+// вместе с заголовком файла при завершении
+struct BufferedBinWriter<W: Write> {
+    writer: W,
+    options: BinWriteOptions,
+    records: Vec<TransactionRecord>,
+}
+
+impl<W: Write> RecordWriter for BufferedBinWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> std::io::Result<()> {
+        self.records.push(record.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        YPBankBinRecords::new(std::mem::take(&mut self.records))
+            .write_to_with_options(&mut self.writer, self.options)
+    }
+}
+
+// Стандартный вывод как файл с поддержкой Seek, если он перенаправлен в обычный файл
+fn stdout_file() -> std::io::Result<Option<File>> {
+    #[cfg(unix)]
+    let handle = std::os::fd::AsFd::as_fd(&stdout()).try_clone_to_owned()?;
+    #[cfg(windows)]
+    let handle = std::os::windows::io::AsHandle::as_handle(&stdout()).try_clone_to_owned()?;
+    let file = File::from(handle);
+    Ok(file.metadata()?.is_file().then_some(file))
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    if cli.record_checksums && cli.bin_version != BinContainer::V2 {
+        return Err("--record-checksums requires --bin-version v2".into());
+    }
+
+    let (input_format, input) =
+        cli_support::open_detected(&cli.input, cli.input_format.map(Format::from))
+            .context("Invalid input file in --input argument")?;
+    let output_format = Format::from(cli.output_format);
+    let bin_v2 = output_format == Format::Bin && cli.bin_version == BinContainer::V2;
+    // BIN версии 2 пишется потоково, а заголовок файла перезаписывается после последней записи,
+    // если стандартный вывод перенаправлен в обычный файл; иначе записи накапливаются в памяти
+    let mut seekable_stdout = if bin_v2 {
+        stdout_file().context("Failed to inspect standard output")?
+    } else {
+        None
+    };
+    // Запись в TXT и BIN учитывает параметры --txt-header, --txt-field-order и --bin-version
+    let mut record_writer = || -> Box<dyn RecordWriter> {
+        match (output_format, seekable_stdout.take()) {
+            (Format::Txt, _) => Box::new(YPBankTxtWriter::with_options(
+                stdout().lock(),
+                cli.txt_options(),
+            )),
+            (Format::Bin, Some(file)) => {
+                Box::new(YPBankBinWriter::seekable(file, cli.bin_options()))
+            }
+            (Format::Bin, None) if bin_v2 => Box::new(BufferedBinWriter {
+                writer: stdout().lock(),
+                options: cli.bin_options(),
+                records: Vec::new(),
+            }),
+            (Format::Bin, None) => Box::new(YPBankBinWriter::with_options(
+                stdout().lock(),
+                cli.bin_options(),
+            )),
+            _ => parser::record_writer(output_format, stdout().lock()),
         }
     };
 
-    match cli.output_format {
-        FileFormat::Csv => {
-            YPBankCsvRecords::new(records)
-                .write_to(&mut stdout())
-                .with_context(|| "Failed to write output as csv records")?;
+    if cli.txt_field_order == TxtOrder::Preserve {
+        if input_format != Format::Txt || output_format != Format::Txt {
+            return Err("--txt-field-order preserve requires txt input and txt output".into());
         }
-        FileFormat::Txt => {
-            YPBankTxtRecords::new(records)
-                .write_to(&mut stdout())
-                .with_context(|| "Failed to write output as txt records")?;
+        if cli.recover || cli.skip_invalid {
+            return Err(
+                "--txt-field-order preserve cannot be combined with --recover or --skip-invalid"
+                    .into(),
+            );
         }
-        FileFormat::Binary => {
-            YPBankBinRecords::new(records)
-                .write_to(&mut stdout())
-                .with_context(|| "Failed to write output as binary records")?;
+        let mut reader = YPBankTxtReader::new(input);
+        let mut writer = YPBankTxtWriter::with_options(stdout().lock(), cli.txt_options());
+        copy_txt_records(&mut reader, &mut writer, &cli)?;
+    } else if cli.recover {
+        if input_format != Format::Bin {
+            return Err("--recover is supported only for binary input".into());
         }
+        let mut reader = YPBankBinReader::with_recovery(input);
+        let mut writer = record_writer();
+        copy_records(&mut reader, writer.as_mut(), input_format, &cli)?;
+        for region in reader.corrupted_regions() {
+            eprintln!(
+                "Skipped corrupted region at offset {} ({} bytes): {}",
+                region.offset, region.length, region.error
+            );
+        }
+    } else if cli.skip_invalid {
+        let mut rejects = match &cli.rejects {
+            Some(path) => Some(BufWriter::new(File::create(path).with_context(|| {
+                format!(
+                    "Failed to create rejects file '{}' specified in --rejects argument",
+                    path
+                )
+            })?)),
+            None => None,
+        };
+        let mut reader = parser::lenient_record_reader(input_format, input);
+        let mut writer = record_writer();
+        let skipped = copy_valid_records(
+            reader.as_mut(),
+            writer.as_mut(),
+            rejects.as_mut().map(|w| w as &mut dyn Write),
+            input_format,
+            &cli,
+        )?;
+        if skipped > 0 {
+            eprintln!("Skipped {} invalid records", skipped);
+        }
+    } else {
+        let mut reader = parser::record_reader(input_format, input);
+        let mut writer = record_writer();
+        copy_records(reader.as_mut(), writer.as_mut(), input_format, &cli)?;
     }
+
     Ok(())
 }
@@ -0,0 +1,94 @@
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use cli_support::FileFormat;
+use parser::ledger::Ledger;
+use parser::{Format, TransactionRecord};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print final balances of all users and detected overdrafts
+    Balances {
+        #[command(flatten)]
+        input: Input,
+    },
+    /// Print the statement of a single user
+    Statement {
+        #[command(flatten)]
+        input: Input,
+
+        #[arg(short, long)]
+        user: u64,
+    },
+}
+
+#[derive(Args)]
+struct Input {
+    #[arg(short, long)]
+    input: String,
+
+    /// Input format; detected from the file contents when omitted
+    #[arg(short = 'f', long = "format", value_enum)]
+    format: Option<FileFormat>,
+}
+
+fn read_records(input: &Input) -> anyhow::Result<Vec<TransactionRecord>> {
+    let (format, file) = cli_support::open_detected(&input.input, input.format.map(Format::from))
+        .context("Invalid input file in --input argument")?;
+
+    let records = parser::record_reader(format, file)
+        .collect::<parser::ParseResult<Vec<TransactionRecord>>>()
+        .with_context(|| {
+            format!(
+                "Failed to parse as {} data from file '{}'",
+                format, input.input
+            )
+        })?;
+    Ok(records)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Balances { input } => {
+            let ledger = Ledger::replay(&read_records(&input)?);
+            println!("{:>20}  {:>24}", "USER_ID", "BALANCE");
+            for (user_id, balance) in ledger.balances() {
+                println!("{:>20}  {:>24}", user_id, balance);
+            }
+            for overdraft in ledger.overdrafts() {
+                eprintln!(
+                    "Overdraft: user {} balance {} after transaction {} at {}",
+                    overdraft.user_id, overdraft.balance, overdraft.tx_id, overdraft.timestamp
+                );
+            }
+        }
+        Command::Statement { input, user } => {
+            let ledger = Ledger::replay(&read_records(&input)?);
+            println!(
+                "{:>20}  {:<10}  {:>16}  {:>24}  {:>24}",
+                "TX_ID", "TX_TYPE", "TIMESTAMP", "CHANGE", "BALANCE"
+            );
+            for entry in ledger.statement(user) {
+                println!(
+                    "{:>20}  {:<10}  {:>16}  {:>24}  {:>24}",
+                    entry.tx_id,
+                    entry.tx_type.to_string(),
+                    entry.timestamp,
+                    entry.change,
+                    entry.balance
+                );
+            }
+            println!("Final balance of user {}: {}", user, ledger.balance(user));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use cli_support::FileFormat;
+use parser::{BinFileReader, BinIndex, Format, IndexSource};
+use std::io::stdout;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Build the tx_id index of a binary file and save it next to the file
+    Index {
+        #[arg(short, long)]
+        input: String,
+
+        /// Index file path; defaults to the input path with `.idx` appended
+        #[arg(long)]
+        index: Option<String>,
+    },
+    /// Print records with the given TX_ID values from a binary file
+    Get {
+        #[arg(short, long)]
+        input: String,
+
+        /// Index file path; defaults to the input path with `.idx` appended.
+        /// A missing or outdated index is rebuilt in memory
+        #[arg(long)]
+        index: Option<String>,
+
+        #[arg(short, long = "tx-id", required = true, num_args = 1..)]
+        tx_ids: Vec<u64>,
+
+        #[arg(short = 'o', long = "output-format", value_enum, default_value_t = FileFormat::Txt)]
+        output_format: FileFormat,
+    },
+}
+
+fn index_path(input: &str, index: &Option<String>) -> String {
+    match index {
+        Some(path) => path.clone(),
+        None => BinIndex::path_for(input).to_string_lossy().into_owned(),
+    }
+}
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Index { input, index } => {
+            let path = index_path(&input, &index);
+            let bin_index = BinIndex::build_file(&input)
+                .with_context(|| format!("Failed to parse as bin data from file '{}'", input))?;
+            bin_index
+                .save(&path)
+                .with_context(|| format!("Failed to write index file '{}'", path))?;
+            println!("Indexed {} records into '{}'", bin_index.len(), path);
+        }
+        Command::Get {
+            input,
+            index,
+            tx_ids,
+            output_format,
+        } => {
+            let path = index_path(&input, &index);
+            let (mut reader, source) = BinFileReader::open_with_index(&input, &path)
+                .with_context(|| format!("Failed to parse as bin data from file '{}'", input))?;
+            match source {
+                IndexSource::Loaded => {}
+                IndexSource::Built => {
+                    eprintln!("Index '{}' is not available, built it in memory", path)
+                }
+                IndexSource::Outdated => {
+                    eprintln!("Index '{}' is outdated, rebuilt it in memory", path)
+                }
+            }
+
+            let output_format = Format::from(output_format);
+            let mut writer = parser::record_writer(output_format, stdout().lock());
+            let mut missing = 0;
+            for tx_id in tx_ids {
+                let record = reader
+                    .get(tx_id)
+                    .with_context(|| format!("Failed to read TX_ID {} from '{}'", tx_id, input))?;
+                match record {
+                    Some(record) => writer.write_record(&record).with_context(|| {
+                        format!("Failed to write output as {} records", output_format)
+                    })?,
+                    None => {
+                        missing += 1;
+                        eprintln!("TX_ID {} not found", tx_id);
+                    }
+                }
+            }
+            writer
+                .finish()
+                .with_context(|| format!("Failed to write output as {} records", output_format))?;
+
+            if missing > 0 {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,169 @@
+//! Общие части утилит командной строки: аргумент формата файла и открытие входного файла
+//! с определением формата по содержимому.
+
+use parser::{DetectFormatError, Format};
+use std::fs::File;
+use std::io::{BufReader, Chain, Cursor, Read};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Формат файла в аргументах командной строки
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum, Debug)]
+pub enum FileFormat {
+    Csv,
+    Txt,
+    Binary,
+    Json,
+    Ndjson,
+}
+
+impl From<FileFormat> for Format {
+    fn from(format: FileFormat) -> Self {
+        match format {
+            FileFormat::Csv => Format::Csv,
+            FileFormat::Txt => Format::Txt,
+            FileFormat::Binary => Format::Bin,
+            FileFormat::Json => Format::Json,
+            FileFormat::Ndjson => Format::Ndjson,
+        }
+    }
+}
+
+/// Ошибка открытия входного файла в [`open_detected`]
+#[derive(Error, Debug)]
+pub enum OpenError {
+    #[error("failed to open input file '{}'", path.display())]
+    Open {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to read input file '{}'", path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to detect format of '{}', specify it explicitly", path.display())]
+    Detect {
+        path: PathBuf,
+        #[source]
+        source: DetectFormatError,
+    },
+}
+
+/// Входной файл: прочитанное для определения формата начало и остаток файла
+pub type Input = BufReader<Chain<Cursor<Vec<u8>>, File>>;
+
+// Размер порции, которой читается начало файла
+const HEAD_CHUNK: usize = 8 * 1024;
+
+/// Открывает входной файл и определяет его формат.
+///
+/// Если `format` не задан, начало файла читается до первой строки, которая не пуста и не является
+/// комментарием `#`, и формат определяется по нему через [`parser::detect_format`]. Возвращённый
+/// читатель начинается с первого байта файла, поэтому файл не обязан поддерживать перемещение.
+pub fn open_detected(
+    path: impl AsRef<Path>,
+    format: Option<Format>,
+) -> Result<(Format, Input), OpenError> {
+    let path = path.as_ref();
+    let mut file = File::open(path).map_err(|source| OpenError::Open {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if let Some(format) = format {
+        return Ok((format, BufReader::new(Cursor::new(Vec::new()).chain(file))));
+    }
+
+    let mut head = Vec::new();
+    loop {
+        let read = (&mut file)
+            .take(HEAD_CHUNK as u64)
+            .read_to_end(&mut head)
+            .map_err(|source| OpenError::Read {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        if read == 0 || !needs_more(&head) {
+            break;
+        }
+    }
+
+    let format = parser::detect_format(&head).map_err(|source| OpenError::Detect {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    Ok((format, BufReader::new(Cursor::new(head).chain(file))))
+}
+
+// Начало файла ещё не содержит первую значимую строку целиком: все строки пусты или являются
+// комментариями TXT, либо первая значимая строка не закончилась и короче порции чтения
+fn needs_more(head: &[u8]) -> bool {
+    let head = head.strip_prefix("\u{feff}".as_bytes()).unwrap_or(head);
+    let mut lines = head.split(|&b| b == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_ascii();
+        if !trimmed.is_empty() && !trimmed.starts_with(b"#") {
+            return lines.peek().is_none() && line.len() < HEAD_CHUNK;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cli-support-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_open_detected() {
+        let path = temp_path("records.csv");
+        let data = "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\n";
+        std::fs::write(&path, data).unwrap();
+
+        let (format, mut reader) = open_detected(&path, None).unwrap();
+        assert_eq!(format, Format::Csv);
+        let mut read = String::new();
+        reader.read_to_string(&mut read).unwrap();
+        assert_eq!(read, data);
+
+        let (format, _) = open_detected(&path, Some(FileFormat::Txt.into())).unwrap();
+        assert_eq!(format, Format::Txt);
+
+        std::fs::write(&path, "garbage").unwrap();
+        assert!(matches!(
+            open_detected(&path, None),
+            Err(OpenError::Detect {
+                source: DetectFormatError::Unknown,
+                ..
+            })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            open_detected(&path, None),
+            Err(OpenError::Open { .. })
+        ));
+    }
+
+    #[test]
+    fn test_long_leading_comments() {
+        let path = temp_path("comments.txt");
+        let mut data = "# comment line\n".repeat(2 * HEAD_CHUNK / 15);
+        data.push_str("TX_ID: 1\nTX_TYPE: DEPOSIT\n");
+        std::fs::write(&path, &data).unwrap();
+
+        let (format, mut reader) = open_detected(&path, None).unwrap();
+        assert_eq!(format, Format::Txt);
+        let mut read = String::new();
+        reader.read_to_string(&mut read).unwrap();
+        assert_eq!(read, data);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Context;
+use clap::Parser;
+use cli_support::FileFormat;
+use parser::validate::{TimestampRangeRule, UniqueTxIdRule, UserAccountsRule, Validator};
+use parser::Format;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[arg(short, long)]
+    input: String,
+
+    /// Input format; detected from the file contents when omitted
+    #[arg(short = 'f', long = "format", value_enum)]
+    format: Option<FileFormat>,
+
+    /// Lower bound of allowed TIMESTAMP values, in milliseconds since the Unix epoch
+    #[arg(long, default_value_t = TimestampRangeRule::DEFAULT_MIN)]
+    min_timestamp: u64,
+
+    /// Upper bound of allowed TIMESTAMP values, in milliseconds since the Unix epoch
+    #[arg(long, default_value_t = TimestampRangeRule::DEFAULT_MAX)]
+    max_timestamp: u64,
+}
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    let (format, input) = cli_support::open_detected(&cli.input, cli.format.map(Format::from))
+        .context("Invalid input file in --input argument")?;
+
+    let mut validator = Validator::new()
+        .rule(UserAccountsRule)
+        .rule(UniqueTxIdRule::default())
+        .rule(TimestampRangeRule::new(
+            cli.min_timestamp,
+            cli.max_timestamp,
+        ));
+
+    for record in parser::record_reader(format, input) {
+        let record = record.with_context(|| {
+            format!(
+                "Failed to parse as {} data from file '{}'",
+                format, cli.input
+            )
+        })?;
+        validator.check(&record);
+    }
+
+    let report = validator.finish();
+    for violation in &report.violations {
+        println!("{}", violation);
+    }
+
+    if report.is_valid() {
+        println!("All {} records are valid.", report.records_checked);
+        Ok(ExitCode::SUCCESS)
+    } else {
+        println!(
+            "Found {} violations in {} records.",
+            report.violations.len(),
+            report.records_checked
+        );
+        Ok(ExitCode::FAILURE)
+    }
+}
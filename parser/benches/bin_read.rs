@@ -0,0 +1,107 @@
+//! Сравнение чтения BIN-файла потоковым читателем и читателем срезов без копирования.
+//!
+//! Число записей в сгенерированном файле задаётся переменной окружения `YPBANK_BENCH_RECORDS`
+//! (по умолчанию 2 000 000): `cargo bench -p parser --features mmap --bench bin_read`.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use parser::{
+    BinSliceReader, RecordParser, RecordWriter, Status, TransactionRecord, TxType, YPBankBinReader,
+    YPBankBinRecords, YPBankBinWriter,
+};
+use std::fs::File;
+use std::hint::black_box;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_RECORDS: u64 = 2_000_000;
+
+fn record_count() -> u64 {
+    std::env::var("YPBANK_BENCH_RECORDS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_RECORDS)
+}
+
+// Создаёт BIN-файл с заданным числом записей во временном каталоге
+fn generate(records: u64) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ypbank-bench-{}.bin", records));
+    if path.exists() {
+        return path;
+    }
+
+    let mut writer = YPBankBinWriter::new(File::create(&path).unwrap());
+    for tx_id in 0..records {
+        let record = TransactionRecord::builder()
+            .tx_id(tx_id)
+            .tx_type(TxType::TRANSFER)
+            .from_user_id(tx_id % 1000 + 1)
+            .to_user_id(tx_id % 777 + 1001)
+            .amount((tx_id % 10_000) as i64)
+            .timestamp(1633036860000 + tx_id)
+            .status(Status::SUCCESS)
+            .description(format!("Record number {}", tx_id))
+            .build()
+            .unwrap();
+        writer.write_record(&record).unwrap();
+    }
+    writer.finish().unwrap();
+    path
+}
+
+fn open(path: &Path) -> BufReader<File> {
+    BufReader::new(File::open(path).unwrap())
+}
+
+fn bin_read(c: &mut Criterion) {
+    let records = record_count();
+    let path = generate(records);
+
+    let mut group = c.benchmark_group("bin_read");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(records));
+
+    group.bench_function("records_from_read", |b| {
+        b.iter(|| {
+            let parsed = YPBankBinRecords::from_read(&mut open(&path)).unwrap();
+            black_box(parsed.records.len())
+        })
+    });
+
+    group.bench_function("stream_reader", |b| {
+        b.iter(|| {
+            let mut amount = 0i64;
+            for record in YPBankBinReader::new(open(&path)) {
+                amount = amount.wrapping_add(record.unwrap().amount());
+            }
+            black_box(amount)
+        })
+    });
+
+    let data = std::fs::read(&path).unwrap();
+    group.bench_function("slice_reader", |b| {
+        b.iter(|| {
+            let mut amount = 0i64;
+            for view in BinSliceReader::new(&data).unwrap() {
+                amount = amount.wrapping_add(view.unwrap().amount);
+            }
+            black_box(amount)
+        })
+    });
+
+    #[cfg(feature = "mmap")]
+    group.bench_function("mmap_reader", |b| {
+        b.iter(|| {
+            let file = parser::MmapBinFile::open(&path).unwrap();
+            let mut amount = 0i64;
+            for view in file.records().unwrap() {
+                amount = amount.wrapping_add(view.unwrap().amount);
+            }
+            black_box(amount)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bin_read);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+//! Сравнение последовательного и параллельного (feature `parallel`) разбора данных в памяти.
+//!
+//! Число записей задаётся переменной окружения `YPBANK_BENCH_RECORDS` (по умолчанию 1 000 000),
+//! число потоков — переменной `RAYON_NUM_THREADS`:
+//! `cargo bench -p parser --features parallel --bench parallel`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use parser::parallel::parse_parallel;
+use parser::{
+    Format, ParseResult, RecordWriter, Status, TransactionRecord, TxType, YPBankBinWriter,
+    YPBankCsvWriter, YPBankTxtWriter,
+};
+use std::hint::black_box;
+
+const DEFAULT_RECORDS: u64 = 1_000_000;
+
+fn record_count() -> u64 {
+    std::env::var("YPBANK_BENCH_RECORDS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_RECORDS)
+}
+
+// Сериализует сгенерированные записи в заданном формате
+fn generate(format: Format, records: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    {
+        let mut writer: Box<dyn RecordWriter + '_> = match format {
+            Format::Bin => Box::new(YPBankBinWriter::new(&mut data)),
+            Format::Csv => Box::new(YPBankCsvWriter::new(&mut data)),
+            Format::Txt => Box::new(YPBankTxtWriter::new(&mut data)),
+            other => unreachable!("{} is not benchmarked", other),
+        };
+        for tx_id in 0..records {
+            let record = TransactionRecord::builder()
+                .tx_id(tx_id)
+                .tx_type(TxType::TRANSFER)
+                .from_user_id(tx_id % 1000 + 1)
+                .to_user_id(tx_id % 777 + 1001)
+                .amount((tx_id % 10_000) as i64)
+                .timestamp(1633036860000 + tx_id)
+                .status(Status::SUCCESS)
+                .description(format!("Record number {}", tx_id))
+                .build()
+                .unwrap();
+            writer.write_record(&record).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    data
+}
+
+fn parse(c: &mut Criterion) {
+    let records = record_count();
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(records));
+
+    for format in [Format::Bin, Format::Csv, Format::Txt] {
+        let data = generate(format, records);
+        group.bench_with_input(BenchmarkId::new("sequential", format), &data, |b, data| {
+            b.iter(|| {
+                let parsed = parser::record_reader(format, &data[..])
+                    .collect::<ParseResult<Vec<_>>>()
+                    .unwrap();
+                black_box(parsed.len())
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", format), &data, |b, data| {
+            b.iter(|| black_box(parse_parallel(format, data).unwrap().len()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
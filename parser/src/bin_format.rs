@@ -1,14 +1,44 @@
 use byteorder::{BigEndian, ReadBytesExt};
-use std::io::{BufReader, BufWriter, Cursor, Error, ErrorKind, Read, Write};
+use crc32fast::Hasher;
+use std::io::{BufWriter, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::error::{BinToTransError, TransToBinError};
-use crate::{RecordParser, Status, TransactionRecord, TxType};
+use crate::bin_view::BinRecordView;
+use crate::builder::check_amount_sign;
+use crate::error::{FieldError, FileHeaderError, ParseError, ParseResult, Position};
+use crate::{Format, RecordParser, RecordReader, RecordWriter, Status, TransactionRecord, TxType};
 
 // Постоянное значение 0x59 0x50 0x42 0x4E ('YPBN'), идентифицирующее заголовок записи.
-const MAGIC: u32 = 0x5950424E;
+pub(crate) const MAGIC: u32 = 0x5950424E;
+
+// Постоянное значение 0x59 0x50 0x42 0x46 ('YPBF'), идентифицирующее заголовок файла версии 2.
+pub(crate) const FILE_MAGIC: u32 = 0x59504246;
+
+// Размер заголовка файла версии 2
+pub(crate) const FILE_HEADER_SIZE: usize = 4 +  // FILE_MAGIC
+        2 +  // version
+        2 +  // flags
+        8 +  // created_at
+        8 +  // record_count
+        4; // body_crc
+
+// Флаг заголовка файла: за телом каждой записи следует CRC32 тела (4 байта)
+pub(crate) const FLAG_RECORD_CRC: u16 = 0x0001;
+
+// Размер заголовка записи: MAGIC + RECORD_SIZE
+pub(crate) const HEADER_SIZE: usize = 4 + 4;
+
+/// Наибольший допустимый `RECORD_SIZE`, в байтах.
+///
+/// Запись с большим размером считается повреждённой ещё до чтения тела,
+/// поэтому повреждённое поле размера не приводит к чтению всего остатка файла в память.
+pub const MAX_RECORD_SIZE: u32 = 16 * 1024 * 1024;
+
+// Размер блока, читаемого из источника за один раз
+const READ_CHUNK: usize = 64 * 1024;
 
 // Размер фиксированной части записи в бинарном формате
-const BODY_FIXED_PART_SIZE: usize = 8 +  // tx_id
+pub(crate) const BODY_FIXED_PART_SIZE: usize = 8 +  // tx_id
         1 +  // tx_type
         8 +  // from_user_id
         8 +  // to_user_id
@@ -32,14 +62,13 @@ pub(crate) struct BinRecord {
     pub(crate) tx_id: u64,
     pub(crate) from_user_id: u64,
     pub(crate) to_user_id: u64,
-    pub(crate) amount: u64,
+    pub(crate) amount: i64,
     pub(crate) timestamp: u64,
     pub(crate) description: String,
 }
 
-impl TryFrom<&TransactionRecord> for BinRecord {
-    type Error = TransToBinError;
-    fn try_from(record: &TransactionRecord) -> Result<Self, Self::Error> {
+impl From<&TransactionRecord> for BinRecord {
+    fn from(record: &TransactionRecord) -> Self {
         let tx_type = match record.tx_type {
             TxType::DEPOSIT => 0,
             TxType::TRANSFER => 1,
@@ -52,7 +81,7 @@ impl TryFrom<&TransactionRecord> for BinRecord {
             Status::PENDING => 2,
         };
 
-        Ok(BinRecord {
+        BinRecord {
             tx_type,
             status,
             desc_len: record.description.len() as u32,
@@ -62,10 +91,91 @@ impl TryFrom<&TransactionRecord> for BinRecord {
             amount: record.amount,
             timestamp: record.timestamp,
             description: record.description.clone(),
-        })
+        }
+    }
+}
+
+/// Версия контейнера BIN-файла
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinVersion {
+    /// Последовательность записей без заголовка файла, как в исходной спецификации
+    #[default]
+    V1,
+    /// Заголовок файла с версией, временем создания, числом записей и контрольной суммой
+    V2,
+}
+
+/// Заголовок BIN-файла версии 2.
+///
+/// Располагается в начале файла перед записями: `FILE_MAGIC` (`YPBF`), `VERSION` (u16),
+/// `FLAGS` (u16, бит 0 — CRC32 после тела каждой записи), `CREATED_AT` (u64, миллисекунды Unix-времени),
+/// `RECORD_COUNT` (u64) и `BODY_CRC` (u32, CRC32 всех байт после заголовка).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinFileHeader {
+    pub version: u16,
+    pub flags: u16,
+    pub created_at: u64,
+    pub record_count: u64,
+    pub body_crc: u32,
+}
+
+impl BinFileHeader {
+    /// `true`, если за телом каждой записи следует её CRC32
+    pub fn has_record_checksums(&self) -> bool {
+        self.flags & FLAG_RECORD_CRC != 0
+    }
+
+    fn to_bytes(self) -> [u8; FILE_HEADER_SIZE] {
+        let mut bytes = [0u8; FILE_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&FILE_MAGIC.to_be_bytes());
+        bytes[4..6].copy_from_slice(&self.version.to_be_bytes());
+        bytes[6..8].copy_from_slice(&self.flags.to_be_bytes());
+        bytes[8..16].copy_from_slice(&self.created_at.to_be_bytes());
+        bytes[16..24].copy_from_slice(&self.record_count.to_be_bytes());
+        bytes[24..28].copy_from_slice(&self.body_crc.to_be_bytes());
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8; FILE_HEADER_SIZE]) -> Self {
+        let mut cursor = Cursor::new(&bytes[4..]);
+        // Длина буфера фиксирована, поэтому чтение из него не может завершиться ошибкой
+        BinFileHeader {
+            version: cursor.read_u16::<BigEndian>().unwrap(),
+            flags: cursor.read_u16::<BigEndian>().unwrap(),
+            created_at: cursor.read_u64::<BigEndian>().unwrap(),
+            record_count: cursor.read_u64::<BigEndian>().unwrap(),
+            body_crc: cursor.read_u32::<BigEndian>().unwrap(),
+        }
+    }
+
+    // Проверяет, что версия и флаги заголовка поддерживаются
+    pub(crate) fn check(&self) -> ParseResult<()> {
+        if self.version != 2 {
+            return Err(ParseError::InvalidFileHeader {
+                reason: FileHeaderError::UnsupportedVersion(self.version),
+            });
+        }
+        if self.flags & !FLAG_RECORD_CRC != 0 {
+            return Err(ParseError::InvalidFileHeader {
+                reason: FileHeaderError::UnsupportedFlags(self.flags),
+            });
+        }
+        Ok(())
     }
 }
 
+/// Параметры записи BIN-файла
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BinWriteOptions {
+    pub version: BinVersion,
+    /// Время создания для заголовка версии 2 в миллисекундах Unix-времени;
+    /// если не задано, используется текущее время
+    pub created_at: Option<u64>,
+    /// Записывать CRC32 после тела каждой записи; поддерживается только версией 2,
+    /// так как наличие контрольных сумм отмечается флагом в заголовке файла
+    pub record_checksums: bool,
+}
+
 /// Коллекция банковских записей, полученная из BIN-файла формата YP Bank.
 ///
 /// Хранит вектор транзакций [`TransactionRecord`](crate::TransactionRecord).
@@ -80,63 +190,819 @@ impl YPBankBinRecords {
     pub fn new(records: Vec<TransactionRecord>) -> Self {
         YPBankBinRecords { records }
     }
+
+    /// Читает записи в режиме восстановления: повреждённые участки пропускаются
+    /// с ресинхронизацией по `MAGIC` и возвращаются вместе с корректными записями.
+    pub fn from_read_recovering<R: Read>(r: &mut R) -> ParseResult<(Self, Vec<CorruptedRegion>)> {
+        let mut reader = YPBankBinReader::with_recovery(r);
+        let records = reader.by_ref().collect::<ParseResult<Vec<_>>>()?;
+
+        Ok((YPBankBinRecords { records }, reader.corrupted))
+    }
+
+    /// Записывает записи в BIN-файл заданной версии
+    pub fn write_to_with_options<W: Write>(
+        &self,
+        writer: &mut W,
+        options: BinWriteOptions,
+    ) -> std::io::Result<()> {
+        if options.version == BinVersion::V1 {
+            let mut bin_writer = YPBankBinWriter::with_options(writer, options);
+            for record in self.records.iter() {
+                bin_writer.write_record(record)?;
+            }
+            return bin_writer.finish();
+        }
+
+        // Записи уже в памяти, поэтому число записей и контрольная сумма тела вычисляются заранее,
+        // и вывод не обязан поддерживать Seek
+        let mut buffer = Vec::new();
+        let mut body_crc = Hasher::new();
+        for record in self.records.iter() {
+            buffer.clear();
+            encode_record(&mut buffer, record, options.record_checksums)?;
+            body_crc.update(&buffer);
+        }
+        let header = file_header(&options, self.records.len() as u64, body_crc.finalize());
+
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&header.to_bytes())?;
+        for record in self.records.iter() {
+            buffer.clear();
+            encode_record(&mut buffer, record, options.record_checksums)?;
+            writer.write_all(&buffer)?;
+        }
+        writer.flush()
+    }
 }
 
 impl RecordParser for YPBankBinRecords {
-    fn from_read<R: Read>(r: &mut R) -> std::io::Result<Self>
+    fn from_read<R: Read>(r: &mut R) -> ParseResult<Self>
     where
         Self: Sized,
     {
-        let mut reader = BufReader::new(r);
-        let records = parse_bin_records(&mut reader)?;
+        let records = YPBankBinReader::new(r).collect::<ParseResult<Vec<_>>>()?;
 
         Ok(YPBankBinRecords { records })
     }
 
     fn write_to<W: Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
-        for record in self.records.iter() {
-            let bin_record = BinRecord::try_from(record)?;
-            write_record_to(writer, &bin_record)?;
+        self.write_to_with_options(writer, BinWriteOptions::default())
+    }
+}
+
+/// Повреждённый участок BIN-файла, пропущенный в режиме восстановления
+#[derive(Debug)]
+pub struct CorruptedRegion {
+    /// Смещение начала участка от начала файла, в байтах
+    pub offset: u64,
+    /// Длина пропущенного участка, в байтах
+    pub length: u64,
+    /// Ошибка, из-за которой участок был пропущен
+    pub error: ParseError,
+}
+
+/// Потоковый читатель BIN-файла формата YP Bank.
+///
+/// Читает записи по одной через [`RecordReader`](crate::RecordReader), не загружая весь файл в память.
+/// После ошибки в заголовке записи (неверный `MAGIC`) граница следующей записи неизвестна,
+/// поэтому чтение прекращается. Ошибка в теле записи не мешает чтению следующих записей.
+///
+/// В режиме восстановления ([`YPBankBinReader::with_recovery`]) повреждённые участки не приводят к ошибке:
+/// читатель ищет следующий заголовок `MAGIC` и продолжает чтение с него,
+/// а пропущенные участки доступны через [`YPBankBinReader::corrupted_regions`].
+/// Читатель из [`YPBankBinReader::skipping_invalid`] так же продолжает чтение, но сообщает о каждом участке ошибкой.
+///
+/// Файлы версии 2 распознаются по заголовку `FILE_MAGIC`; после последней записи число записей
+/// и контрольная сумма сверяются с заголовком файла. В режиме восстановления расхождение
+/// добавляется в список повреждённых участков.
+pub struct YPBankBinReader<R: Read> {
+    reader: FrameBuffer<R>,
+    // Заголовок файла версии 2; None для файлов без заголовка
+    header: Option<BinFileHeader>,
+    // Заголовок файла уже прочитан (или его отсутствие установлено)
+    started: bool,
+    // Число записей и контрольная сумма ещё не сверены с заголовком файла
+    pending_check: bool,
+    // Число прочитанных записей, включая повреждённые
+    frames: u64,
+    recover: bool,
+    skip_invalid: bool,
+    corrupted: Vec<CorruptedRegion>,
+    // Исходные байты последней отклонённой записи
+    rejected: Vec<u8>,
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    // Смещение начала последней прочитанной записи
+    record_offset: u64,
+    finished: bool,
+}
+
+impl<R: Read> YPBankBinReader<R> {
+    pub fn new(r: R) -> Self {
+        YPBankBinReader {
+            reader: FrameBuffer::new(r),
+            header: None,
+            started: false,
+            pending_check: false,
+            frames: 0,
+            recover: false,
+            skip_invalid: false,
+            corrupted: Vec::new(),
+            rejected: Vec::new(),
+            record: 0,
+            record_offset: 0,
+            finished: false,
+        }
+    }
+
+    // Создаёт читатель, начинающий чтение с записи по смещению offset файла с заголовком header
+    pub(crate) fn at_record(r: R, header: Option<BinFileHeader>, offset: u64) -> Self {
+        let mut reader = YPBankBinReader::new(r);
+        reader.reader.offset = offset;
+        reader.header = header;
+        reader.started = true;
+        reader
+    }
+
+    /// Создаёт читатель, пропускающий повреждённые участки с ресинхронизацией по `MAGIC`
+    pub fn with_recovery(r: R) -> Self {
+        YPBankBinReader {
+            recover: true,
+            ..YPBankBinReader::new(r)
         }
+    }
+
+    /// Создаёт читатель для нестрогого режима: о каждой повреждённой записи сообщается ошибкой,
+    /// после чего чтение продолжается со следующего заголовка `MAGIC`.
+    /// Пропущенные байты доступны через [`RecordReader::take_rejected`].
+    pub fn skipping_invalid(r: R) -> Self {
+        YPBankBinReader {
+            skip_invalid: true,
+            ..YPBankBinReader::new(r)
+        }
+    }
 
+    /// Участки, пропущенные в режиме восстановления к текущему моменту чтения
+    pub fn corrupted_regions(&self) -> &[CorruptedRegion] {
+        &self.corrupted
+    }
+
+    /// Заголовок файла версии 2; `None` для файлов без заголовка или до чтения первой записи
+    pub fn file_header(&self) -> Option<&BinFileHeader> {
+        self.header.as_ref()
+    }
+
+    /// Смещение от начала файла, с которого начинается последняя прочитанная запись
+    pub fn record_offset(&self) -> u64 {
+        self.record_offset
+    }
+
+    // Смещение следующего непрочитанного байта от начала источника
+    pub(crate) fn position(&self) -> u64 {
+        self.reader.offset
+    }
+
+    // Читает заголовок файла версии 2, если данные начинаются с FILE_MAGIC
+    pub(crate) fn read_file_header(&mut self) -> ParseResult<()> {
+        let available = self.reader.fill(FILE_HEADER_SIZE)?;
+        if !self.reader.data().starts_with(&FILE_MAGIC.to_be_bytes()) {
+            return Ok(());
+        }
+
+        let Some(bytes) = self.reader.data().first_chunk::<FILE_HEADER_SIZE>() else {
+            return Err(ParseError::InvalidFileHeader {
+                reason: FileHeaderError::Truncated {
+                    expected: FILE_HEADER_SIZE,
+                    actual: available,
+                },
+            });
+        };
+        let header = BinFileHeader::from_bytes(bytes);
+        header.check()?;
+        self.reader.consume(FILE_HEADER_SIZE);
+
+        // Байты после заголовка, уже прочитанные в буфер, в сумму ещё не вошли
+        let mut crc = Hasher::new();
+        crc.update(self.reader.data());
+        self.reader.crc = Some(crc);
+        self.header = Some(header);
+        self.pending_check = true;
         Ok(())
     }
+
+    // Сверяет число записей и контрольную сумму с заголовком файла версии 2
+    fn check_file(&mut self) -> ParseResult<()> {
+        let (Some(header), Some(crc)) = (self.header, self.reader.crc.take()) else {
+            return Ok(());
+        };
+
+        let actual = crc.finalize();
+        let error = if self.frames != header.record_count {
+            ParseError::RecordCountMismatch {
+                expected: header.record_count,
+                actual: self.frames,
+            }
+        } else if actual != header.body_crc {
+            ParseError::BodyChecksumMismatch {
+                expected: header.body_crc,
+                actual,
+            }
+        } else {
+            return Ok(());
+        };
+
+        if self.recover {
+            self.corrupted.push(CorruptedRegion {
+                offset: FILE_HEADER_SIZE as u64,
+                length: self.reader.offset - FILE_HEADER_SIZE as u64,
+                error,
+            });
+            return Ok(());
+        }
+        Err(error)
+    }
+
+    fn read_framed(&mut self, record: usize, offset: u64) -> std::io::Result<Framed> {
+        self.reader.discard_consumed();
+        let checksums = self.header.is_some_and(|h| h.has_record_checksums());
+        let mut needed = HEADER_SIZE;
+        loop {
+            let available = self.reader.fill(needed)?;
+            if available == 0 || (available < HEADER_SIZE && !self.recover && !self.skip_invalid) {
+                self.reader.consume(available);
+                return Ok(Framed::End);
+            }
+
+            // Меньше needed байт fill возвращает только в конце источника
+            let eof = available < needed;
+            match split_frame(self.reader.data(), record, offset, checksums, eof) {
+                Frame::Incomplete { len } => needed = len,
+                Frame::Complete { body, len } => {
+                    let parsed = parse_record_from_bytes(body, record, offset);
+                    self.reader.consume(len);
+                    return Ok(match parsed {
+                        Ok(record) => Framed::Record(record),
+                        Err(error) => Framed::Corrupted {
+                            error,
+                            consumed: len,
+                            fatal: false,
+                        },
+                    });
+                }
+                Frame::Corrupted {
+                    error,
+                    consumed,
+                    fatal,
+                } => {
+                    self.reader.consume(consumed);
+                    return Ok(Framed::Corrupted {
+                        error,
+                        consumed,
+                        fatal,
+                    });
+                }
+            }
+        }
+    }
+
+    // Ищет следующий MAGIC начиная с текущей позиции. Возвращает число пропущенных байт.
+    // Пропущенные байты остаются в буфере для take_rejected, кроме режима восстановления
+    fn resync(&mut self) -> std::io::Result<usize> {
+        let magic = MAGIC.to_be_bytes();
+        let mut skipped = 0;
+        loop {
+            if self.recover {
+                self.reader.discard_consumed();
+            }
+            let available = self.reader.fill(magic.len())?;
+            let data = self.reader.data();
+            if let Some(found) = data.windows(magic.len()).position(|w| w == magic) {
+                self.reader.consume(found);
+                return Ok(skipped + found);
+            }
+            if available < magic.len() {
+                self.reader.consume(available);
+                self.finished = true;
+                return Ok(skipped + available);
+            }
+            // Последние байты могут оказаться началом MAGIC, дочитанного следующим блоком
+            let scanned = available - (magic.len() - 1);
+            self.reader.consume(scanned);
+            skipped += scanned;
+        }
+    }
+}
+
+impl<R: Read> RecordReader for YPBankBinReader<R> {
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>> {
+        if !self.started {
+            self.started = true;
+            if let Err(e) = self.read_file_header() {
+                self.finished = true;
+                return Err(e);
+            }
+        }
+
+        loop {
+            if self.finished {
+                if self.pending_check {
+                    self.pending_check = false;
+                    self.check_file()?;
+                }
+                return Ok(None);
+            }
+
+            let offset = self.reader.offset;
+            self.record += 1;
+            self.record_offset = offset;
+            let framed = self.read_framed(self.record, offset);
+            if let Ok(Framed::Record(_) | Framed::Corrupted { .. }) = framed {
+                self.frames += 1;
+            }
+            match framed {
+                Ok(Framed::Record(record)) => return Ok(Some(record)),
+                Ok(Framed::End) => {
+                    self.finished = true;
+                    continue;
+                }
+                Ok(Framed::Corrupted {
+                    error,
+                    consumed,
+                    fatal,
+                }) => {
+                    let resync = self.recover || (fatal && self.skip_invalid);
+                    if !resync {
+                        self.finished = fatal;
+                        // После потери границы записей сверка с заголовком файла бессмысленна
+                        self.pending_check &= !fatal;
+                        self.rejected = self.reader.consumed(consumed).to_vec();
+                        return Err(error);
+                    }
+                    // Поиск следующей записи начинается с байта, следующего за началом повреждённой
+                    self.reader.rewind(consumed - 1);
+                    let skipped = self.resync()?;
+                    if self.recover {
+                        self.corrupted.push(CorruptedRegion {
+                            offset,
+                            length: 1 + skipped as u64,
+                            error,
+                        });
+                        continue;
+                    }
+                    self.rejected = self.reader.consumed(1 + skipped).to_vec();
+                    return Err(error);
+                }
+                Err(e) => {
+                    self.finished = true;
+                    self.pending_check = false;
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    fn take_rejected(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rejected)
+    }
+}
+
+// Результат чтения одной записи с учётом её заголовка
+enum Framed {
+    Record(TransactionRecord),
+    End,
+    // Повреждённая запись и число байт, потреблённых при попытке её прочитать.
+    // fatal означает, что граница следующей записи неизвестна
+    Corrupted {
+        error: ParseError,
+        consumed: usize,
+        fatal: bool,
+    },
+}
+
+// Результат выделения записи из начала данных в split_frame
+pub(crate) enum Frame<'a> {
+    // Тело записи без заголовка и контрольной суммы и полная длина записи
+    Complete {
+        body: &'a [u8],
+        len: usize,
+    },
+    // Данных меньше len байт, нужных для записи, но источник ещё не закончился
+    Incomplete {
+        len: usize,
+    },
+    // Повреждённая запись и число байт, которые нужно пропустить.
+    // fatal означает, что граница следующей записи неизвестна
+    Corrupted {
+        error: ParseError,
+        consumed: usize,
+        fatal: bool,
+    },
 }
 
-fn parse_bin_records<R: Read>(r: &mut R) -> std::io::Result<Vec<TransactionRecord>> {
-    let mut records = Vec::new();
-    loop {
-        let header = match read_bin_header(r) {
-            Ok(header) => header,
-            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
-            Err(e) => return Err(e),
+// Проверяет заголовок записи и возвращает её полную длину вместе с заголовком и контрольной суммой
+pub(crate) fn frame_len(
+    header: &[u8; HEADER_SIZE],
+    record: usize,
+    offset: u64,
+    checksums: bool,
+) -> ParseResult<usize> {
+    let header = parse_bin_header(header);
+    if header.magic != MAGIC {
+        return Err(ParseError::InvalidMagic {
+            record,
+            offset,
+            magic: header.magic,
+        });
+    }
+    if header.record_size > MAX_RECORD_SIZE {
+        return Err(ParseError::RecordTooLarge {
+            record,
+            offset,
+            size: header.record_size,
+            max: MAX_RECORD_SIZE,
+        });
+    }
+    let trailer = if checksums { 4 } else { 0 };
+    Ok(HEADER_SIZE + header.record_size as usize + trailer)
+}
+
+// Выделяет запись с заголовком MAGIC и RECORD_SIZE из начала data: проверяет заголовок записи,
+// её полноту и, если checksums, контрольную сумму тела. eof означает, что за data данных нет,
+// и тогда неполная запись считается обрезанной. Общая часть потокового и срезового читателей
+pub(crate) fn split_frame(
+    data: &[u8],
+    record: usize,
+    offset: u64,
+    checksums: bool,
+    eof: bool,
+) -> Frame<'_> {
+    let truncated = |part, expected: usize, actual: usize| Frame::Corrupted {
+        error: ParseError::Truncated {
+            record,
+            offset,
+            part,
+            expected: expected as u64,
+            actual: actual as u64,
+        },
+        consumed: data.len(),
+        fatal: true,
+    };
+
+    let Some(header_bytes) = data.first_chunk::<HEADER_SIZE>() else {
+        if !eof {
+            return Frame::Incomplete { len: HEADER_SIZE };
+        }
+        return truncated("record header", HEADER_SIZE, data.len());
+    };
+
+    let len = match frame_len(header_bytes, record, offset, checksums) {
+        Ok(len) => len,
+        Err(error) => {
+            return Frame::Corrupted {
+                error,
+                consumed: HEADER_SIZE,
+                fatal: true,
+            };
+        }
+    };
+    let trailer = if checksums { 4 } else { 0 };
+    let record_size = len - HEADER_SIZE - trailer;
+    if data.len() < len {
+        if !eof {
+            return Frame::Incomplete { len };
+        }
+        let body = data.len() - HEADER_SIZE;
+        return if body < record_size {
+            truncated("record body", record_size, body)
+        } else {
+            truncated("record checksum", trailer, body - record_size)
         };
+    }
 
-        if header.magic != MAGIC {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("Invalid magic 0x{:X}", header.magic),
-            ));
+    let (body, stored) = data[HEADER_SIZE..len].split_at(record_size);
+    // Тело короче TX_ID не пройдёт разбор, поэтому сумма проверяется только у полных записей
+    if let (Some(stored), Some(tx_id)) = (stored.first_chunk::<4>(), body.first_chunk::<8>()) {
+        let expected = u32::from_be_bytes(*stored);
+        let actual = crc32fast::hash(body);
+        if expected != actual {
+            return Frame::Corrupted {
+                error: ParseError::RecordChecksumMismatch {
+                    record,
+                    offset,
+                    tx_id: u64::from_be_bytes(*tx_id),
+                    expected,
+                    actual,
+                },
+                consumed: len,
+                fatal: false,
+            };
+        }
+    }
+    Frame::Complete { body, len }
+}
+
+// Буферизованный источник записей.
+// Прочитанные байты остаются в буфере до начала чтения следующей записи, поэтому запись разбирается
+// прямо из буфера, а при ресинхронизации к её байтам можно вернуться без копирования
+struct FrameBuffer<R: Read> {
+    inner: R,
+    buf: Vec<u8>,
+    // Позиция чтения в buf
+    pos: usize,
+    // Смещение байта buf[pos] от начала источника
+    offset: u64,
+    // Контрольная сумма байт, прочитанных из источника после включения подсчёта
+    crc: Option<Hasher>,
+}
+
+impl<R: Read> FrameBuffer<R> {
+    fn new(r: R) -> Self {
+        FrameBuffer {
+            inner: r,
+            buf: Vec::new(),
+            pos: 0,
+            offset: 0,
+            crc: None,
+        }
+    }
+
+    // Непрочитанные байты буфера
+    fn data(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    // Последние n прочитанных байт
+    fn consumed(&self, n: usize) -> &[u8] {
+        &self.buf[self.pos - n..self.pos]
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.pos += n;
+        self.offset += n as u64;
+    }
+
+    fn rewind(&mut self, n: usize) {
+        self.pos -= n;
+        self.offset -= n as u64;
+    }
+
+    // Отбрасывает прочитанные байты, к которым больше не нужно возвращаться.
+    // Буфер сдвигается, только когда прочитанная часть не меньше оставшейся,
+    // поэтому каждый байт в среднем копируется не более одного раза
+    fn discard_consumed(&mut self) {
+        if self.pos >= READ_CHUNK && self.pos * 2 >= self.buf.len() {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+    }
+
+    // Дочитывает источник, пока в буфере не окажется хотя бы n непрочитанных байт.
+    // Возвращает число непрочитанных байт; меньше n — только в конце источника
+    fn fill(&mut self, n: usize) -> std::io::Result<usize> {
+        while self.buf.len() - self.pos < n {
+            let len = self.buf.len();
+            let missing = n - (len - self.pos);
+            self.buf.resize(len + missing.max(READ_CHUNK), 0);
+            let read = loop {
+                match self.inner.read(&mut self.buf[len..]) {
+                    Ok(read) => break read,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        self.buf.truncate(len);
+                        return Err(e);
+                    }
+                }
+            };
+            self.buf.truncate(len + read);
+            if let Some(crc) = self.crc.as_mut() {
+                crc.update(&self.buf[len..]);
+            }
+            if read == 0 {
+                break;
+            }
         }
+        Ok(self.buf.len() - self.pos)
+    }
+}
+
+/// Потоковый писатель BIN-файла формата YP Bank.
+///
+/// Каждая запись сразу сериализуется с заголовком `MAGIC` и `RECORD_SIZE` и выводится.
+/// Для версии 2 число записей и контрольная сумма известны только после последней записи,
+/// поэтому перед первой записью выводится заголовок файла с нулевыми значениями, а при вызове
+/// [`RecordWriter::finish`] он перезаписывается. Для этого вывод должен поддерживать [`Seek`]:
+/// писатель версии 2 создаётся через [`YPBankBinWriter::seekable`], а писатель из
+/// [`YPBankBinWriter::with_options`] отклоняет запись версии 2 ошибкой [`ErrorKind::Unsupported`].
+///
+/// Повторный вызов [`RecordWriter::finish`] ничего не выводит, а запись после него отклоняется.
+pub struct YPBankBinWriter<W: Write> {
+    writer: BufWriter<W>,
+    options: BinWriteOptions,
+    // None, если вывод не поддерживает Seek
+    seek: Option<SeekFn<W>>,
+    // Заголовок файла версии 2, уже выведенный перед записями, и его смещение в выводе
+    header: Option<BinFileHeader>,
+    header_offset: u64,
+    // Сериализованная запись и контрольная сумма тела файла версии 2
+    record: Vec<u8>,
+    body_crc: Hasher,
+    finished: bool,
+}
+
+impl<W: Write> YPBankBinWriter<W> {
+    pub fn new(w: W) -> Self {
+        YPBankBinWriter::with_options(w, BinWriteOptions::default())
+    }
+
+    pub fn with_options(w: W, options: BinWriteOptions) -> Self {
+        YPBankBinWriter {
+            writer: BufWriter::new(w),
+            options,
+            seek: None,
+            header: None,
+            header_offset: 0,
+            record: Vec::new(),
+            body_crc: Hasher::new(),
+            finished: false,
+        }
+    }
+
+    /// Писатель в вывод с [`Seek`], поддерживающий все версии контейнера
+    pub fn seekable(w: W, options: BinWriteOptions) -> Self
+    where
+        W: Seek,
+    {
+        YPBankBinWriter {
+            seek: Some(<W as Seek>::seek),
+            ..YPBankBinWriter::with_options(w, options)
+        }
+    }
+
+    // Выводит заголовок файла версии 2 с нулевыми числом записей и контрольной суммой
+    fn start_file(&mut self) -> std::io::Result<()> {
+        if self.header.is_some() {
+            return Ok(());
+        }
+        let Some(seek) = self.seek else {
+            return Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "bin version 2 requires a seekable output",
+            ));
+        };
+        self.writer.flush()?;
+        self.header_offset = seek(self.writer.get_mut(), SeekFrom::Current(0))?;
+        let header = file_header(&self.options, 0, 0);
+        self.writer.write_all(&header.to_bytes())?;
+        self.header = Some(header);
+        Ok(())
+    }
+}
+
+// Перемещение в выводе писателя версии 2 (Seek::seek)
+type SeekFn<W> = fn(&mut W, SeekFrom) -> std::io::Result<u64>;
 
-        let mut buffer = vec![0u8; header.record_size as usize];
-        r.read_exact(&mut buffer)?;
+// Перезаписывает заголовок файла по смещению offset и возвращается в конец вывода.
+// В режиме добавления (O_APPEND) запись попадает в конец файла независимо от позиции,
+// поэтому позиция после записи проверяется
+fn patch_header<W: Write>(
+    w: &mut W,
+    seek: SeekFn<W>,
+    offset: u64,
+    header: &[u8],
+) -> std::io::Result<()> {
+    let end = seek(w, SeekFrom::Current(0))?;
+    seek(w, SeekFrom::Start(offset))?;
+    w.write_all(header)?;
+    if seek(w, SeekFrom::Current(0))? != offset + header.len() as u64 {
+        return Err(std::io::Error::new(
+            ErrorKind::Unsupported,
+            "bin version 2 output must not be opened in append mode",
+        ));
+    }
+    seek(w, SeekFrom::Start(end))?;
+    Ok(())
+}
+
+// Заголовок файла версии 2 с заданными числом записей и контрольной суммой тела
+fn file_header(options: &BinWriteOptions, record_count: u64, body_crc: u32) -> BinFileHeader {
+    let created_at = options.created_at.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+    });
+    BinFileHeader {
+        version: 2,
+        flags: if options.record_checksums {
+            FLAG_RECORD_CRC
+        } else {
+            0
+        },
+        created_at,
+        record_count,
+        body_crc,
+    }
+}
+
+// Сериализует запись в конец buffer; с checksum за телом записи следует его CRC32
+fn encode_record(
+    buffer: &mut Vec<u8>,
+    record: &TransactionRecord,
+    checksum: bool,
+) -> std::io::Result<()> {
+    let start = buffer.len();
+    write_record_to(buffer, &BinRecord::from(record))?;
+    if checksum {
+        let crc = crc32fast::hash(&buffer[start + HEADER_SIZE..]);
+        buffer.extend_from_slice(&crc.to_be_bytes());
+    }
+    Ok(())
+}
 
-        let record = parse_record_from_bytes(buffer.as_slice())?;
-        records.push(record);
+impl<W: Write> RecordWriter for YPBankBinWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> std::io::Result<()> {
+        if self.finished {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "bin writer is already finished",
+            ));
+        }
+        let v2 = self.options.version == BinVersion::V2;
+        self.record.clear();
+        encode_record(
+            &mut self.record,
+            record,
+            v2 && self.options.record_checksums,
+        )?;
+        if v2 {
+            self.start_file()?;
+            if let Some(header) = self.header.as_mut() {
+                header.record_count += 1;
+            }
+            self.body_crc.update(&self.record);
+        }
+        self.writer.write_all(&self.record)
     }
 
-    Ok(records)
+    fn finish(&mut self) -> std::io::Result<()> {
+        if self.finished {
+            return self.writer.flush();
+        }
+        if self.options.version == BinVersion::V2 {
+            self.start_file()?;
+        }
+        // Заголовок перезаписывается один раз, даже если это завершится ошибкой
+        self.finished = true;
+        if let (Some(header), Some(seek)) = (self.header.as_mut(), self.seek) {
+            header.body_crc = std::mem::take(&mut self.body_crc).finalize();
+            let bytes = header.to_bytes();
+            self.writer.flush()?;
+            patch_header(self.writer.get_mut(), seek, self.header_offset, &bytes)?;
+        }
+        self.writer.flush()
+    }
 }
 
-fn read_bin_header<R: Read>(r: &mut R) -> std::io::Result<BinHeader> {
-    let magic = r.read_u32::<BigEndian>()?;
-    let record_size = r.read_u32::<BigEndian>()?;
+fn parse_bin_header(bytes: &[u8; HEADER_SIZE]) -> BinHeader {
+    let magic = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let record_size = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+    BinHeader { magic, record_size }
+}
 
-    Ok(BinHeader { magic, record_size })
+fn parse_record_from_bytes(
+    bytes: &[u8],
+    record: usize,
+    offset: u64,
+) -> ParseResult<TransactionRecord> {
+    parse_record_view(bytes, record, offset).map(TransactionRecord::from)
 }
 
-fn parse_record_from_bytes(bytes: &[u8]) -> std::io::Result<TransactionRecord> {
+// Разбирает тело записи без копирования описания
+pub(crate) fn parse_record_view(
+    bytes: &[u8],
+    record: usize,
+    offset: u64,
+) -> ParseResult<BinRecordView<'_>> {
+    let invalid = |field: &'static str, value: String, reason| ParseError::InvalidField {
+        format: Format::Bin,
+        record,
+        position: Position::Offset(offset),
+        field,
+        value,
+        reason,
+    };
+
+    if bytes.len() < BODY_FIXED_PART_SIZE {
+        return Err(ParseError::Truncated {
+            record,
+            offset,
+            part: "record body",
+            expected: BODY_FIXED_PART_SIZE as u64,
+            actual: bytes.len() as u64,
+        });
+    }
+
     let mut cursor = Cursor::new(bytes);
 
     let tx_id = cursor.read_u64::<BigEndian>()?;
@@ -144,45 +1010,66 @@ fn parse_record_from_bytes(bytes: &[u8]) -> std::io::Result<TransactionRecord> {
         0 => TxType::DEPOSIT,
         1 => TxType::TRANSFER,
         2 => TxType::WITHDRAWAL,
-        other => return Err(BinToTransError::InvalidTxType(other).into()),
+        other => {
+            return Err(invalid(
+                "TX_TYPE",
+                other.to_string(),
+                FieldError::UnknownValue,
+            ));
+        }
     };
 
     let from_user_id = cursor.read_u64::<BigEndian>()?;
     let to_user_id = cursor.read_u64::<BigEndian>()?;
 
-    let amount = cursor.read_u64::<BigEndian>()?;
+    let amount = cursor.read_i64::<BigEndian>()?;
     let timestamp = cursor.read_u64::<BigEndian>()?;
 
     let status = match cursor.read_u8()? {
         0 => Status::SUCCESS,
         1 => Status::FAILURE,
         2 => Status::PENDING,
-        other => return Err(Error::from(BinToTransError::InvalidStatus(other))),
+        other => {
+            return Err(invalid(
+                "STATUS",
+                other.to_string(),
+                FieldError::UnknownValue,
+            ));
+        }
     };
 
     let desc_len = cursor.read_u32::<BigEndian>()?;
 
     // Проверяем, что осталось достаточно байт для описания
-    let remaining_bytes = bytes.len() - cursor.position() as usize;
+    let start = cursor.position() as usize;
+    let remaining_bytes = bytes.len() - start;
     if desc_len as usize > remaining_bytes {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            format!(
-                "Not enough bytes for description: need {}, have {}",
-                desc_len, remaining_bytes
-            ),
-        ));
+        return Err(ParseError::Truncated {
+            record,
+            offset,
+            part: "description",
+            expected: desc_len as u64,
+            actual: remaining_bytes as u64,
+        });
     }
 
-    let mut description_bytes = vec![0u8; desc_len as usize];
-    cursor.read_exact(&mut description_bytes)?;
+    let description_bytes = &bytes[start..start + desc_len as usize];
+    let description = std::str::from_utf8(description_bytes).map_err(|_| {
+        let value = String::from_utf8_lossy(description_bytes).into_owned();
+        invalid("DESCRIPTION", value, FieldError::InvalidUtf8)
+    })?;
 
-    let description =
-        String::from_utf8(description_bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let description = description.trim_matches('"');
 
-    let description = description.trim_matches('"').to_string();
+    check_amount_sign(tx_type, amount).map_err(|reason| ParseError::InvalidAmount {
+        format: Format::Bin,
+        record,
+        position: Position::Offset(offset),
+        value: amount.to_string(),
+        reason,
+    })?;
 
-    Ok(TransactionRecord {
+    Ok(BinRecordView {
         tx_type,
         status,
         tx_id,
@@ -194,10 +1081,17 @@ fn parse_record_from_bytes(bytes: &[u8]) -> std::io::Result<TransactionRecord> {
     })
 }
 
-fn write_record_to<W: Write>(w: &mut W, record: &BinRecord) -> std::io::Result<()> {
-    let mut buffer = BufWriter::new(w);
-
+fn write_record_to<W: Write>(buffer: &mut W, record: &BinRecord) -> std::io::Result<()> {
     let body_size = BODY_FIXED_PART_SIZE + record.desc_len as usize;
+    if body_size > MAX_RECORD_SIZE as usize {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "record TX_ID {} is {} bytes, maximum is {}",
+                record.tx_id, body_size, MAX_RECORD_SIZE
+            ),
+        ));
+    }
 
     buffer.write_all(&MAGIC.to_be_bytes())?;
     buffer.write_all(&(body_size as u32).to_be_bytes())?;
@@ -211,7 +1105,6 @@ fn write_record_to<W: Write>(w: &mut W, record: &BinRecord) -> std::io::Result<(
     buffer.write_all(&[record.status])?;
     buffer.write_all(&record.desc_len.to_be_bytes())?;
     buffer.write_all(record.description.as_bytes())?;
-    buffer.flush()?;
 
     Ok(())
 }
@@ -219,6 +1112,7 @@ fn write_record_to<W: Write>(w: &mut W, record: &BinRecord) -> std::io::Result<(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::AmountError;
     use std::io::Cursor;
 
     #[test]
@@ -244,6 +1138,224 @@ mod tests {
         assert_eq!(test_bin_records, buff_record);
     }
 
+    #[test]
+    fn test_reader_stops_after_invalid_magic() {
+        let mut test_bin_records = YPBankBinRecords {
+            records: vec![TransactionRecord {
+                tx_type: TxType::WITHDRAWAL,
+                status: Status::SUCCESS,
+                tx_id: 7,
+                from_user_id: 42,
+                to_user_id: 0,
+                amount: -500,
+                timestamp: 1633096800000,
+                description: String::new(),
+            }],
+        };
+
+        let mut data = Vec::new();
+        test_bin_records.write_to(&mut data).unwrap();
+        data.extend_from_slice(&0xDEADBEEFu32.to_be_bytes());
+        data.extend_from_slice(&(100u32).to_be_bytes());
+
+        let mut reader = YPBankBinReader::new(Cursor::new(data));
+        assert_eq!(reader.read_record().unwrap().unwrap().tx_id, 7);
+        assert!(reader.read_record().is_err());
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    fn sample_record(tx_id: u64) -> TransactionRecord {
+        TransactionRecord {
+            tx_type: TxType::TRANSFER,
+            status: Status::SUCCESS,
+            tx_id,
+            from_user_id: 1,
+            to_user_id: 2,
+            amount: 100,
+            timestamp: 1633096800000,
+            description: format!("Record number {}", tx_id),
+        }
+    }
+
+    #[test]
+    fn test_recovery_skips_corrupted_header() {
+        let mut records = YPBankBinRecords::new(vec![sample_record(1), sample_record(2)]);
+        let mut first = Vec::new();
+        records.write_to(&mut first).unwrap();
+
+        let mut data = first.clone();
+        let garbage = b"\x00garbage\xFF";
+        data.extend_from_slice(garbage);
+        // Повреждаем MAGIC второй записи
+        let second_record_offset = first.len() / 2;
+        data[second_record_offset] = 0;
+        data.extend_from_slice(&first);
+
+        let (recovered, regions) =
+            YPBankBinRecords::from_read_recovering(&mut Cursor::new(data)).unwrap();
+
+        let ids: Vec<u64> = recovered.records.iter().map(|r| r.tx_id).collect();
+        assert_eq!(ids, vec![1, 1, 2]);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].offset, second_record_offset as u64);
+        assert_eq!(
+            regions[0].length,
+            (first.len() - second_record_offset + garbage.len()) as u64
+        );
+        assert!(matches!(
+            regions[0].error,
+            ParseError::InvalidMagic { record: 2, offset, .. } if offset == second_record_offset as u64
+        ));
+    }
+
+    #[test]
+    fn test_recovery_skips_invalid_body_and_truncated_tail() {
+        let mut records = YPBankBinRecords::new(vec![sample_record(1), sample_record(2)]);
+        let mut data = Vec::new();
+        records.write_to(&mut data).unwrap();
+
+        // Недопустимый tx_type в первой записи: HEADER_SIZE + tx_id
+        data[HEADER_SIZE + 8] = 9;
+        let full_len = data.len();
+        data.truncate(full_len - 3);
+
+        let mut reader = YPBankBinReader::with_recovery(Cursor::new(data));
+        assert!(reader.read_record().unwrap().is_none());
+
+        let regions = reader.corrupted_regions();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].offset, 0);
+        assert_eq!(regions[0].length, (full_len / 2) as u64);
+        assert!(matches!(
+            &regions[0].error,
+            ParseError::InvalidField { field: "TX_TYPE", value, .. } if value == "9"
+        ));
+        assert_eq!(regions[1].offset, (full_len / 2) as u64);
+        assert!(matches!(
+            regions[1].error,
+            ParseError::Truncated {
+                record: 2,
+                part: "record body",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_oversized_record_size() {
+        let mut first = Vec::new();
+        YPBankBinRecords::new(vec![sample_record(1)])
+            .write_to(&mut first)
+            .unwrap();
+        let mut data = first.clone();
+        data.extend_from_slice(&MAGIC.to_be_bytes());
+        data.extend_from_slice(&u32::MAX.to_be_bytes());
+        let mut second = Vec::new();
+        YPBankBinRecords::new(vec![sample_record(2)])
+            .write_to(&mut second)
+            .unwrap();
+        data.extend_from_slice(&second);
+
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::RecordTooLarge {
+                record: 2,
+                size: u32::MAX,
+                max: MAX_RECORD_SIZE,
+                offset,
+            } if offset == first.len() as u64
+        ));
+
+        let (recovered, regions) =
+            YPBankBinRecords::from_read_recovering(&mut Cursor::new(&data)).unwrap();
+        let ids: Vec<u64> = recovered.records.iter().map(|r| r.tx_id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].length, HEADER_SIZE as u64);
+    }
+
+    #[test]
+    fn test_resync_over_long_garbage() {
+        // Мусор длиннее блока чтения, в котором много ложных MAGIC с слишком коротким телом
+        let mut garbage = Vec::new();
+        while garbage.len() < 3 * READ_CHUNK {
+            garbage.extend_from_slice(&MAGIC.to_be_bytes());
+            garbage.extend_from_slice(&3u32.to_be_bytes());
+            garbage.extend_from_slice(b"xyz\x00");
+        }
+        let mut data = garbage.clone();
+        YPBankBinRecords::new(vec![sample_record(1)])
+            .write_to(&mut data)
+            .unwrap();
+
+        let (recovered, regions) =
+            YPBankBinRecords::from_read_recovering(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(recovered.records.len(), 1);
+        // Каждая ложная запись — отдельный участок, вместе они покрывают весь мусор
+        assert_eq!(
+            regions.iter().map(|r| r.length).sum::<u64>(),
+            garbage.len() as u64
+        );
+
+        let (records, issues) = crate::parse_lenient(Format::Bin, Cursor::new(&data)).unwrap();
+        assert_eq!(records.len(), 1);
+        let raw: Vec<u8> = issues.iter().flat_map(|i| i.raw.clone()).collect();
+        assert_eq!(raw, garbage);
+    }
+
+    #[test]
+    fn test_signed_amount() {
+        let mut withdrawal = sample_record(1);
+        withdrawal.tx_type = TxType::WITHDRAWAL;
+        withdrawal.to_user_id = 0;
+        withdrawal.amount = -300;
+        let mut records = YPBankBinRecords::new(vec![withdrawal]);
+
+        let mut data = Vec::new();
+        records.write_to(&mut data).unwrap();
+        let read = YPBankBinRecords::from_read(&mut Cursor::new(data.clone())).unwrap();
+        assert_eq!(read.records[0].amount, -300);
+
+        // Списание, записанное положительным числом, — ошибка
+        let amount = HEADER_SIZE + 8 + 1 + 8 + 8;
+        data[amount..amount + 8].copy_from_slice(&300i64.to_be_bytes());
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(data.clone())).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidAmount {
+                reason: AmountError::PositiveWithdrawal,
+                ref value,
+                ..
+            } if value == "300"
+        ));
+
+        // Превращаем запись в TRANSFER с отрицательной суммой
+        data[amount..amount + 8].copy_from_slice(&(-300i64).to_be_bytes());
+        data[HEADER_SIZE + 8] = 1;
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(data.clone())).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidAmount {
+                reason: AmountError::NegativeTransfer,
+                ..
+            }
+        ));
+
+        // Превращаем запись в DEPOSIT с отрицательной суммой
+        data[HEADER_SIZE + 8] = 0;
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidAmount {
+                format: Format::Bin,
+                reason: AmountError::NegativeDeposit,
+                ref value,
+                ..
+            } if value == "-300"
+        ));
+    }
+
     #[test]
     fn test_invalid_magic() {
         let mut data = Vec::new();
@@ -254,9 +1366,14 @@ mod tests {
         let result = YPBankBinRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        let msg = err.to_string();
-        assert!(msg.contains("Invalid magic 0xDEADBEEF"));
+        assert!(matches!(
+            err,
+            ParseError::InvalidMagic {
+                record: 1,
+                offset: 0,
+                magic: 0xDEADBEEF
+            }
+        ));
     }
 
     #[test]
@@ -283,11 +1400,17 @@ mod tests {
         let result = YPBankBinRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        assert!(
-            err.to_string()
-                .contains("Invalid transaction type value: 3")
-        );
+        assert!(matches!(
+            err,
+            ParseError::InvalidField {
+                format: Format::Bin,
+                record: 1,
+                position: Position::Offset(0),
+                field: "TX_TYPE",
+                ref value,
+                reason: FieldError::UnknownValue,
+            } if value == "3"
+        ));
     }
 
     #[test]
@@ -311,11 +1434,15 @@ mod tests {
         let result = YPBankBinRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        assert!(
-            err.to_string()
-                .contains("Invalid transaction status value: 5")
-        );
+        assert!(matches!(
+            err,
+            ParseError::InvalidField {
+                field: "STATUS",
+                ref value,
+                reason: FieldError::UnknownValue,
+                ..
+            } if value == "5"
+        ));
     }
 
     #[test]
@@ -340,10 +1467,229 @@ mod tests {
         let result = YPBankBinRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(matches!(
+            err,
+            ParseError::Truncated {
+                part: "description",
+                expected: 10,
+                actual: 5,
+                ..
+            }
+        ));
         assert!(
             err.to_string()
-                .contains("Not enough bytes for description: need 10, have 5")
+                .contains("not enough bytes for description: need 10, have 5")
+        );
+    }
+
+    #[test]
+    fn test_skipping_invalid_reports_corrupted_regions() {
+        let mut records = YPBankBinRecords::new(vec![sample_record(1), sample_record(2)]);
+        let mut data = Vec::new();
+        records.write_to(&mut data).unwrap();
+        let record_len = data.len() / 2;
+
+        // Повреждаем MAGIC первой записи и добавляем запись с недопустимым tx_type
+        data[0] = 0;
+        let mut bad = Vec::new();
+        YPBankBinRecords::new(vec![sample_record(3)])
+            .write_to(&mut bad)
+            .unwrap();
+        bad[HEADER_SIZE + 8] = 9;
+        data.extend_from_slice(&bad);
+
+        let (records, issues) = crate::parse_lenient(Format::Bin, Cursor::new(data)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tx_id, 2);
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(
+            issues[0].error,
+            ParseError::InvalidMagic { offset: 0, .. }
+        ));
+        assert_eq!(issues[0].raw.len(), record_len);
+        assert!(matches!(
+            issues[1].error,
+            ParseError::InvalidField {
+                field: "TX_TYPE",
+                ..
+            }
+        ));
+        assert_eq!(issues[1].raw, bad);
+    }
+
+    const V2: BinWriteOptions = BinWriteOptions {
+        version: BinVersion::V2,
+        created_at: Some(1633096800000),
+        record_checksums: false,
+    };
+
+    fn write_v2(records: &[TransactionRecord]) -> Vec<u8> {
+        let mut data = Vec::new();
+        YPBankBinRecords::new(records.to_vec())
+            .write_to_with_options(&mut data, V2)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_read_write_v2_container() {
+        let records = vec![sample_record(1), sample_record(2)];
+        let data = write_v2(&records);
+        assert!(data.starts_with(b"YPBF\x00\x02\x00\x00"));
+
+        let mut reader = YPBankBinReader::new(Cursor::new(&data));
+        let read = reader.by_ref().collect::<ParseResult<Vec<_>>>().unwrap();
+        assert_eq!(read, records);
+        let header = reader.file_header().unwrap();
+        assert_eq!(header.created_at, 1633096800000);
+        assert_eq!(header.record_count, 2);
+        assert_eq!(header.body_crc, crc32fast::hash(&data[FILE_HEADER_SIZE..]));
+
+        let legacy = include_bytes!("../../test_files/records_example.bin");
+        let mut reader = YPBankBinReader::new(Cursor::new(legacy));
+        assert!(reader.by_ref().all(|r| r.is_ok()));
+        assert!(reader.file_header().is_none());
+    }
+
+    #[test]
+    fn test_seekable_v2_writer() {
+        let records = vec![sample_record(1), sample_record(2)];
+        let options = BinWriteOptions {
+            record_checksums: true,
+            ..V2
+        };
+        let mut expected = Vec::new();
+        YPBankBinRecords::new(records.clone())
+            .write_to_with_options(&mut expected, options)
+            .unwrap();
+
+        // Заголовок перезаписывается по смещению, с которого начался файл, поэтому данные перед ним не затрагиваются
+        let mut output = Cursor::new(b"prefix".to_vec());
+        output.set_position(6);
+        let mut writer = YPBankBinWriter::seekable(&mut output, options);
+        for record in &records {
+            writer.write_record(record).unwrap();
+        }
+        writer.finish().unwrap();
+        // Повторное завершение ничего не выводит, а запись после него — ошибка
+        writer.finish().unwrap();
+        assert_eq!(
+            writer.write_record(&records[0]).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        drop(writer);
+
+        let data = output.into_inner();
+        assert_eq!(&data[..6], b"prefix");
+        assert_eq!(&data[6..], expected);
+
+        let mut writer = YPBankBinWriter::with_options(Vec::new(), V2);
+        assert_eq!(
+            writer.write_record(&records[0]).unwrap_err().kind(),
+            ErrorKind::Unsupported
         );
+        assert_eq!(writer.finish().unwrap_err().kind(), ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_seekable_v2_writer_rejects_append_mode() {
+        let path = std::env::temp_dir().join(format!("ypbank-append-{}.bin", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+        let file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+
+        let mut writer = YPBankBinWriter::seekable(file, V2);
+        writer.write_record(&sample_record(1)).unwrap();
+        assert_eq!(writer.finish().unwrap_err().kind(), ErrorKind::Unsupported);
+        drop(writer);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_v2_body_checksum_mismatch() {
+        let mut data = write_v2(&[sample_record(1)]);
+        // Младший байт AMOUNT: запись остаётся корректной, но контрольная сумма не совпадает
+        let amount = FILE_HEADER_SIZE + HEADER_SIZE + 8 + 1 + 8 + 8 + 7;
+        data[amount] ^= 0x01;
+
+        let mut reader = YPBankBinReader::new(Cursor::new(&data));
+        assert_eq!(reader.read_record().unwrap().unwrap().amount, 101);
+        assert!(matches!(
+            reader.read_record(),
+            Err(ParseError::BodyChecksumMismatch { .. })
+        ));
+        assert!(reader.read_record().unwrap().is_none());
+
+        let (records, corrupted) =
+            YPBankBinRecords::from_read_recovering(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(records.records.len(), 1);
+        assert_eq!(corrupted.len(), 1);
+        assert_eq!(corrupted[0].offset, FILE_HEADER_SIZE as u64);
+        assert!(matches!(
+            corrupted[0].error,
+            ParseError::BodyChecksumMismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_v2_record_count_and_version() {
+        let mut data = write_v2(&[sample_record(1), sample_record(2)]);
+        data[23] = 3;
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::RecordCountMismatch {
+                expected: 3,
+                actual: 2
+            }
+        ));
+
+        data[5] = 9;
+        let err = YPBankBinRecords::from_read(&mut Cursor::new(&data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidFileHeader {
+                reason: FileHeaderError::UnsupportedVersion(9)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_record_checksum_mismatch() {
+        let options = BinWriteOptions {
+            record_checksums: true,
+            ..V2
+        };
+        let records = vec![sample_record(1), sample_record(2), sample_record(3)];
+        let mut data = Vec::new();
+        YPBankBinRecords::new(records.clone())
+            .write_to_with_options(&mut data, options)
+            .unwrap();
+        assert_eq!(data[7], 0x01);
+
+        let read = YPBankBinRecords::from_read(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(read.records, records);
+
+        // Младший байт AMOUNT второй записи
+        let record_len = HEADER_SIZE + BODY_FIXED_PART_SIZE + "Record number 1".len() + 4;
+        let second = FILE_HEADER_SIZE + record_len;
+        data[second + HEADER_SIZE + 8 + 1 + 8 + 8 + 7] ^= 0x01;
+
+        let (read, issues) = crate::parse_lenient(Format::Bin, Cursor::new(&data)).unwrap();
+        let ids: Vec<u64> = read.iter().map(|r| r.tx_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert!(matches!(
+            issues[0].error,
+            ParseError::RecordChecksumMismatch { record: 2, tx_id: 2, offset, .. }
+                if offset == second as u64
+        ));
+        assert_eq!(issues[0].raw.len(), record_len);
+        // Сумма тела файла тоже не совпадает
+        assert!(matches!(
+            issues[1].error,
+            ParseError::BodyChecksumMismatch { .. }
+        ));
     }
 }
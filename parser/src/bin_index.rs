@@ -0,0 +1,369 @@
+use byteorder::{BigEndian, ReadBytesExt};
+use std::collections::BTreeMap;
+use std::fs::{File, Metadata};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::bin_format::BinFileHeader;
+use crate::error::{IndexError, ParseError, ParseResult};
+use crate::{RecordReader, TransactionRecord, YPBankBinReader};
+
+// Постоянное значение 0x59 0x50 0x42 0x49 ('YPBI'), идентифицирующее файл индекса.
+const INDEX_MAGIC: u32 = 0x59504249;
+
+/// Индекс BIN-файла: смещение начала каждой записи по её `TX_ID`.
+///
+/// Если `TX_ID` встречается в файле несколько раз, индекс указывает на первую запись.
+/// Индекс сохраняется в отдельный файл рядом с данными ([`BinIndex::path_for`]):
+/// `INDEX_MAGIC` (`YPBI`), число записей (u64), размер BIN-файла (u64), время его изменения
+/// (u64, наносекунды Unix-времени), затем пары `TX_ID` (u64) и смещение (u64) в порядке возрастания `TX_ID`,
+/// всё в big-endian. По размеру и времени изменения определяется, что индекс устарел.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BinIndex {
+    offsets: BTreeMap<u64, u64>,
+    // Размер и время изменения BIN-файла, по которому построен индекс; 0, если время неизвестно
+    data_len: u64,
+    data_modified: u64,
+}
+
+impl BinIndex {
+    /// Строит индекс, прочитав все записи BIN-файла
+    pub fn build<R: Read>(r: R) -> ParseResult<Self> {
+        let mut reader = YPBankBinReader::new(r);
+        let mut offsets = BTreeMap::new();
+        while let Some(record) = reader.read_record()? {
+            offsets
+                .entry(record.tx_id)
+                .or_insert(reader.record_offset());
+        }
+
+        Ok(BinIndex {
+            offsets,
+            data_len: reader.position(),
+            data_modified: 0,
+        })
+    }
+
+    /// Строит индекс BIN-файла и запоминает его время изменения
+    pub fn build_file(path: impl AsRef<Path>) -> ParseResult<Self> {
+        let file = File::open(path)?;
+        let modified = modified_nanos(&file.metadata()?);
+        let mut index = BinIndex::build(BufReader::new(file))?;
+        index.data_modified = modified;
+        Ok(index)
+    }
+
+    /// `true`, если индекс построен для BIN-файла с такими размером и временем изменения
+    pub fn is_current(&self, metadata: &Metadata) -> bool {
+        self.data_len == metadata.len() && self.data_modified == modified_nanos(metadata)
+    }
+
+    /// Путь файла индекса для BIN-файла: к имени файла добавляется `.idx`
+    pub fn path_for(data: impl AsRef<Path>) -> PathBuf {
+        let mut path = data.as_ref().as_os_str().to_owned();
+        path.push(".idx");
+        PathBuf::from(path)
+    }
+
+    /// Смещение записи с заданным `TX_ID` от начала BIN-файла
+    pub fn get(&self, tx_id: u64) -> Option<u64> {
+        self.offsets.get(&tx_id).copied()
+    }
+
+    /// Количество проиндексированных `TX_ID`
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Размер BIN-файла, по которому построен индекс, в байтах
+    pub fn data_len(&self) -> u64 {
+        self.data_len
+    }
+
+    /// Время изменения BIN-файла, по которому построен индекс, в наносекундах Unix-времени;
+    /// `0` для индекса из [`BinIndex::build`]
+    pub fn data_modified(&self) -> u64 {
+        self.data_modified
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&INDEX_MAGIC.to_be_bytes())?;
+        w.write_all(&(self.offsets.len() as u64).to_be_bytes())?;
+        w.write_all(&self.data_len.to_be_bytes())?;
+        w.write_all(&self.data_modified.to_be_bytes())?;
+        for (tx_id, offset) in &self.offsets {
+            w.write_all(&tx_id.to_be_bytes())?;
+            w.write_all(&offset.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(r: &mut R) -> ParseResult<Self> {
+        let invalid = |reason| ParseError::InvalidIndex { reason };
+        let truncated = |e: std::io::Error| match e.kind() {
+            ErrorKind::UnexpectedEof => invalid(IndexError::Truncated),
+            _ => e.into(),
+        };
+
+        let magic = r.read_u32::<BigEndian>().map_err(truncated)?;
+        if magic != INDEX_MAGIC {
+            return Err(invalid(IndexError::InvalidMagic(magic)));
+        }
+        let count = r.read_u64::<BigEndian>().map_err(truncated)?;
+        let data_len = r.read_u64::<BigEndian>().map_err(truncated)?;
+        let data_modified = r.read_u64::<BigEndian>().map_err(truncated)?;
+
+        let mut offsets = BTreeMap::new();
+        for _ in 0..count {
+            let tx_id = r.read_u64::<BigEndian>().map_err(truncated)?;
+            let offset = r.read_u64::<BigEndian>().map_err(truncated)?;
+            if offset >= data_len {
+                return Err(invalid(IndexError::OffsetBeyondData { tx_id, offset }));
+            }
+            offsets.insert(tx_id, offset);
+        }
+
+        Ok(BinIndex {
+            offsets,
+            data_len,
+            data_modified,
+        })
+    }
+
+    /// Сохраняет индекс в файл
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    /// Загружает индекс из файла
+    pub fn load(path: impl AsRef<Path>) -> ParseResult<Self> {
+        BinIndex::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// Читатель BIN-файла с произвольным доступом к записям по `TX_ID` через [`BinIndex`].
+///
+/// Для каждой записи выполняется переход к её смещению и разбор только этой записи,
+/// поэтому время поиска не зависит от размера файла.
+pub struct BinFileReader<R: Read + Seek> {
+    inner: R,
+    index: BinIndex,
+    header: Option<BinFileHeader>,
+}
+
+/// Откуда взят индекс при открытии BIN-файла через [`BinFileReader::open_with_index`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSource {
+    /// Загружен из файла индекса
+    Loaded,
+    /// Файл индекса отсутствует или повреждён, индекс построен в памяти
+    Built,
+    /// Файл индекса построен для другой версии BIN-файла, индекс построен заново в памяти
+    Outdated,
+}
+
+impl BinFileReader<File> {
+    /// Открывает BIN-файл с индексом из файла [`BinIndex::path_for`]
+    pub fn open(path: impl AsRef<Path>) -> ParseResult<Self> {
+        let path = path.as_ref();
+        BinFileReader::open_with_index(path, BinIndex::path_for(path)).map(|(reader, _)| reader)
+    }
+
+    /// Открывает BIN-файл. Индекс загружается из файла `index`, если он есть и построен для BIN-файла
+    /// с теми же размером и временем изменения ([`BinIndex::is_current`]); иначе индекс строится заново в памяти.
+    pub fn open_with_index(
+        path: impl AsRef<Path>,
+        index: impl AsRef<Path>,
+    ) -> ParseResult<(Self, IndexSource)> {
+        let file = File::open(path.as_ref())?;
+        let metadata = file.metadata()?;
+
+        let (index, source) = match BinIndex::load(index) {
+            Ok(index) if index.is_current(&metadata) => (index, IndexSource::Loaded),
+            Ok(_) => (BinIndex::build_file(path)?, IndexSource::Outdated),
+            Err(_) => (BinIndex::build_file(path)?, IndexSource::Built),
+        };
+        Ok((BinFileReader::new(file, index)?, source))
+    }
+}
+
+// Время изменения файла в наносекундах Unix-времени; 0, если платформа его не сообщает
+fn modified_nanos(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos() as u64)
+}
+
+impl<R: Read + Seek> BinFileReader<R> {
+    /// Создаёт читатель по источнику и индексу, построенному для него
+    pub fn new(mut inner: R, index: BinIndex) -> ParseResult<Self> {
+        inner.seek(SeekFrom::Start(0))?;
+        let mut reader = YPBankBinReader::new(&mut inner);
+        reader.read_file_header()?;
+        let header = reader.file_header().copied();
+
+        Ok(BinFileReader {
+            inner,
+            index,
+            header,
+        })
+    }
+
+    pub fn index(&self) -> &BinIndex {
+        &self.index
+    }
+
+    /// Читает запись с заданным `TX_ID`; `None`, если её нет в индексе
+    pub fn get(&mut self, tx_id: u64) -> ParseResult<Option<TransactionRecord>> {
+        let Some(offset) = self.index.get(tx_id) else {
+            return Ok(None);
+        };
+
+        self.inner.seek(SeekFrom::Start(offset))?;
+        let mut reader = YPBankBinReader::at_record(&mut self.inner, self.header, offset);
+        let Some(record) = reader.read_record()? else {
+            return Err(ParseError::InvalidIndex {
+                reason: IndexError::OffsetBeyondData { tx_id, offset },
+            });
+        };
+        if record.tx_id != tx_id {
+            return Err(ParseError::IndexMismatch {
+                tx_id,
+                offset,
+                found: record.tx_id,
+            });
+        }
+        Ok(Some(record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinVersion, BinWriteOptions, RecordParser, Status, TxType, YPBankBinRecords};
+    use std::io::Cursor;
+
+    fn sample_records() -> Vec<TransactionRecord> {
+        (1..=5)
+            .map(|tx_id| TransactionRecord {
+                tx_type: TxType::DEPOSIT,
+                status: Status::SUCCESS,
+                tx_id: 100 - tx_id,
+                from_user_id: 0,
+                to_user_id: tx_id,
+                amount: tx_id as i64 * 10,
+                timestamp: 1633096800000,
+                description: "x".repeat(tx_id as usize),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_index_lookup() {
+        let records = sample_records();
+        let options = BinWriteOptions {
+            version: BinVersion::V2,
+            created_at: Some(0),
+            record_checksums: true,
+        };
+        let mut data = Vec::new();
+        YPBankBinRecords::new(records.clone())
+            .write_to_with_options(&mut data, options)
+            .unwrap();
+
+        let index = BinIndex::build(Cursor::new(&data)).unwrap();
+        assert_eq!(index.len(), 5);
+        assert_eq!(index.data_len(), data.len() as u64);
+
+        let mut saved = Vec::new();
+        index.write_to(&mut saved).unwrap();
+        let loaded = BinIndex::read_from(&mut Cursor::new(saved)).unwrap();
+        assert_eq!(loaded, index);
+
+        let mut reader = BinFileReader::new(Cursor::new(&data), loaded).unwrap();
+        for record in records.iter().rev() {
+            assert_eq!(reader.get(record.tx_id).unwrap().as_ref(), Some(record));
+        }
+        assert!(reader.get(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_stale_index_detected() {
+        let mut records = YPBankBinRecords::new(sample_records());
+        let mut data = Vec::new();
+        records.write_to(&mut data).unwrap();
+        let index = BinIndex::build(Cursor::new(&data)).unwrap();
+
+        records.records.swap(0, 1);
+        let mut changed = Vec::new();
+        records.write_to(&mut changed).unwrap();
+
+        let mut reader = BinFileReader::new(Cursor::new(changed), index).unwrap();
+        assert!(matches!(
+            reader.get(99),
+            Err(ParseError::IndexMismatch {
+                tx_id: 99,
+                offset: 0,
+                found: 98
+            })
+        ));
+    }
+
+    #[test]
+    fn test_open_with_index_file() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("parser-index-{}.bin", std::process::id()));
+        let index_path = BinIndex::path_for(&path);
+        let mut data = Vec::new();
+        YPBankBinRecords::new(sample_records())
+            .write_to(&mut data)
+            .unwrap();
+        std::fs::write(&path, &data).unwrap();
+
+        let (_, source) = BinFileReader::open_with_index(&path, &index_path).unwrap();
+        assert_eq!(source, IndexSource::Built);
+
+        BinIndex::build_file(&path)
+            .unwrap()
+            .save(&index_path)
+            .unwrap();
+        let (mut reader, source) = BinFileReader::open_with_index(&path, &index_path).unwrap();
+        assert_eq!(source, IndexSource::Loaded);
+        assert_eq!(reader.get(99).unwrap().unwrap().tx_id, 99);
+
+        // Тот же размер, но другое время изменения: индекс считается устаревшим
+        let file = File::options().write(true).open(&path).unwrap();
+        let modified = file.metadata().unwrap().modified().unwrap();
+        file.set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        let (_, source) = BinFileReader::open_with_index(&path, &index_path).unwrap();
+        assert_eq!(source, IndexSource::Outdated);
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&index_path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_index_file() {
+        let err = BinIndex::read_from(&mut Cursor::new(b"YPBI\0\0")).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidIndex {
+                reason: IndexError::Truncated
+            }
+        ));
+        assert_eq!(
+            BinIndex::path_for("data/records.bin"),
+            PathBuf::from("data/records.bin.idx")
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use crate::bin_format::{
+    BinFileHeader, FILE_HEADER_SIZE, FILE_MAGIC, Frame, HEADER_SIZE, parse_record_view, split_frame,
+};
+use crate::error::{FileHeaderError, ParseError, ParseResult};
+use crate::{Status, TransactionRecord, TxType};
+
+/// Запись BIN-файла, поля которой ссылаются на исходные байты без копирования.
+///
+/// Получается из [`BinSliceReader`]; описание — срез исходных данных.
+/// Для хранения записи отдельно от данных её можно преобразовать в [`TransactionRecord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinRecordView<'a> {
+    pub tx_type: TxType,
+    pub status: Status,
+    pub tx_id: u64,
+    pub from_user_id: u64,
+    pub to_user_id: u64,
+    pub amount: i64,
+    pub timestamp: u64,
+    pub description: &'a str,
+}
+
+impl From<BinRecordView<'_>> for TransactionRecord {
+    fn from(view: BinRecordView<'_>) -> Self {
+        TransactionRecord {
+            tx_type: view.tx_type,
+            status: view.status,
+            tx_id: view.tx_id,
+            from_user_id: view.from_user_id,
+            to_user_id: view.to_user_id,
+            amount: view.amount,
+            timestamp: view.timestamp,
+            description: view.description.to_string(),
+        }
+    }
+}
+
+/// Читатель BIN-данных, целиком находящихся в памяти (например, отображённого файла).
+///
+/// Записи возвращаются итератором как [`BinRecordView`] без выделения памяти на каждую запись.
+/// Поведение при ошибках совпадает с [`YPBankBinReader::new`](crate::YPBankBinReader::new):
+/// после ошибки в заголовке записи чтение прекращается, ошибка в теле записи не мешает чтению следующих,
+/// а для файлов версии 2 в конце сверяются число записей и контрольные суммы.
+pub struct BinSliceReader<'a> {
+    data: &'a [u8],
+    // Смещение следующей записи
+    pos: usize,
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    header: Option<BinFileHeader>,
+    // Сверять число записей и контрольную сумму с заголовком файла в конце данных
+    check: bool,
+    finished: bool,
+}
+
+impl<'a> BinSliceReader<'a> {
+    /// Создаёт читатель; для файла версии 2 сразу разбирается заголовок файла
+    pub fn new(data: &'a [u8]) -> ParseResult<Self> {
+        let mut reader = BinSliceReader {
+            data,
+            pos: 0,
+            record: 0,
+            header: None,
+            check: true,
+            finished: false,
+        };
+
+        if data.starts_with(&FILE_MAGIC.to_be_bytes()) {
+            let Some(bytes) = data.first_chunk::<FILE_HEADER_SIZE>() else {
+                return Err(ParseError::InvalidFileHeader {
+                    reason: FileHeaderError::Truncated {
+                        expected: FILE_HEADER_SIZE,
+                        actual: data.len(),
+                    },
+                });
+            };
+            let header = BinFileHeader::from_bytes(bytes);
+            header.check()?;
+            reader.header = Some(header);
+            reader.pos = FILE_HEADER_SIZE;
+        }
+        Ok(reader)
+    }
+
+    // Создаёт читатель части записей файла с заголовком header, без сверки с заголовком файла
+    #[cfg(feature = "parallel")]
+    pub(crate) fn chunk(data: &'a [u8], header: Option<BinFileHeader>) -> Self {
+        BinSliceReader {
+            data,
+            pos: 0,
+            record: 0,
+            header,
+            check: false,
+            finished: false,
+        }
+    }
+
+    /// Заголовок файла версии 2; `None` для файлов без заголовка
+    pub fn file_header(&self) -> Option<&BinFileHeader> {
+        self.header.as_ref()
+    }
+
+    /// Читает следующую запись
+    pub fn read_view(&mut self) -> ParseResult<Option<BinRecordView<'a>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let offset = self.pos;
+        let rest = &self.data[offset..];
+        // Неполный заголовок в конце данных, как и в потоковом читателе, считается концом файла
+        if rest.len() < HEADER_SIZE {
+            self.finished = true;
+            return self.check_file().map(|_| None);
+        }
+        self.record += 1;
+
+        let checksums = self.header.is_some_and(|h| h.has_record_checksums());
+        match split_frame(rest, self.record, offset as u64, checksums, true) {
+            Frame::Complete { body, len } => {
+                self.pos += len;
+                parse_record_view(body, self.record, offset as u64).map(Some)
+            }
+            Frame::Corrupted {
+                error,
+                consumed,
+                fatal,
+            } => {
+                self.finished = fatal;
+                self.pos += consumed;
+                Err(error)
+            }
+            Frame::Incomplete { .. } => unreachable!("split_frame called with eof"),
+        }
+    }
+
+    // Сверяет число записей и контрольную сумму с заголовком файла версии 2
+    fn check_file(&self) -> ParseResult<()> {
+        match self.header {
+            Some(header) if self.check => check_body(self.data, &header, self.record as u64),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Сверяет число записей и контрольную сумму файла версии 2 data с его заголовком
+pub(crate) fn check_body(data: &[u8], header: &BinFileHeader, records: u64) -> ParseResult<()> {
+    if records != header.record_count {
+        return Err(ParseError::RecordCountMismatch {
+            expected: header.record_count,
+            actual: records,
+        });
+    }
+    let actual = crc32fast::hash(&data[FILE_HEADER_SIZE..]);
+    if actual != header.body_crc {
+        return Err(ParseError::BodyChecksumMismatch {
+            expected: header.body_crc,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+impl<'a> Iterator for BinSliceReader<'a> {
+    type Item = ParseResult<BinRecordView<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_view().transpose()
+    }
+}
+
+/// BIN-файл, отображённый в память.
+///
+/// Записи читаются через [`MmapBinFile::records`] прямо из отображения, без буферизации и копирования.
+/// Файл не должен изменяться, пока отображение существует.
+#[cfg(feature = "mmap")]
+pub struct MmapBinFile {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapBinFile {
+    pub fn open(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: файл открыт только на чтение; изменение файла другим процессом во время
+        // отображения — нарушение контракта типа, описанное в документации
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(MmapBinFile { map })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    pub fn records(&self) -> ParseResult<BinSliceReader<'_>> {
+        BinSliceReader::new(&self.map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bin_format::BODY_FIXED_PART_SIZE;
+    use crate::{BinVersion, BinWriteOptions, RecordParser, YPBankBinRecords};
+
+    fn sample_records() -> Vec<TransactionRecord> {
+        (1..=3)
+            .map(|tx_id| TransactionRecord {
+                tx_type: TxType::TRANSFER,
+                status: Status::PENDING,
+                tx_id,
+                from_user_id: 1,
+                to_user_id: 2,
+                amount: 100,
+                timestamp: 1633096800000,
+                description: format!("Record number {}", tx_id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_views_match_stream_reader() {
+        let example = include_bytes!("../../test_files/records_example.bin");
+        let views = BinSliceReader::new(example)
+            .unwrap()
+            .map(|view| view.map(TransactionRecord::from))
+            .collect::<ParseResult<Vec<_>>>()
+            .unwrap();
+        let records = YPBankBinRecords::from_read(&mut &example[..]).unwrap();
+        assert_eq!(views, records.records);
+
+        let mut reader = BinSliceReader::new(example).unwrap();
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.description, "Record number 1");
+        assert!(std::ptr::eq(
+            first.description.as_bytes().as_ptr(),
+            // Описания в примере записаны в кавычках
+            example[HEADER_SIZE + BODY_FIXED_PART_SIZE + 1..].as_ptr()
+        ));
+    }
+
+    #[test]
+    fn test_v2_with_checksums() {
+        let options = BinWriteOptions {
+            version: BinVersion::V2,
+            created_at: Some(0),
+            record_checksums: true,
+        };
+        let mut data = Vec::new();
+        YPBankBinRecords::new(sample_records())
+            .write_to_with_options(&mut data, options)
+            .unwrap();
+
+        let reader = BinSliceReader::new(&data).unwrap();
+        assert_eq!(reader.file_header().unwrap().record_count, 3);
+        assert_eq!(reader.filter(Result::is_ok).count(), 3);
+
+        // Байт описания второй записи
+        let second =
+            FILE_HEADER_SIZE + HEADER_SIZE + BODY_FIXED_PART_SIZE + "Record number 1".len() + 4;
+        data[second + HEADER_SIZE + BODY_FIXED_PART_SIZE] ^= 0x20;
+        let results: Vec<_> = BinSliceReader::new(&data).unwrap().collect();
+        assert_eq!(results.len(), 4);
+        assert!(matches!(
+            results[1],
+            Err(ParseError::RecordChecksumMismatch { tx_id: 2, .. })
+        ));
+        assert!(matches!(
+            results[3],
+            Err(ParseError::BodyChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_truncated_and_invalid_data() {
+        let mut data = Vec::new();
+        YPBankBinRecords::new(sample_records())
+            .write_to(&mut data)
+            .unwrap();
+        data.truncate(data.len() - 3);
+
+        let results: Vec<_> = BinSliceReader::new(&data).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            results[2],
+            Err(ParseError::Truncated {
+                record: 3,
+                part: "record body",
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            BinSliceReader::new(b"YPBF\0\x02"),
+            Err(ParseError::InvalidFileHeader {
+                reason: FileHeaderError::Truncated {
+                    expected: FILE_HEADER_SIZE,
+                    actual: 6
+                }
+            })
+        ));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_file() {
+        let path = std::env::temp_dir().join(format!("ypbank-mmap-{}.bin", std::process::id()));
+        std::fs::write(
+            &path,
+            include_bytes!("../../test_files/records_example.bin"),
+        )
+        .unwrap();
+
+        let file = MmapBinFile::open(&path).unwrap();
+        let count = file.records().unwrap().filter(Result::is_ok).count();
+        assert_eq!(count, 1000);
+        drop(file);
+        std::fs::remove_file(path).unwrap();
+    }
+}
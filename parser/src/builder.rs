@@ -0,0 +1,293 @@
+use crate::error::{AmountError, RecordBuildError};
+use crate::{Status, TransactionRecord, TxType};
+
+/// Построитель [`TransactionRecord`](crate::TransactionRecord).
+///
+/// Обязательные поля: `tx_id`, `tx_type`, `amount`, `timestamp`, `status`.
+/// По умолчанию `from_user_id` и `to_user_id` равны `0`, описание пустое.
+/// При вызове [`build`](TransactionRecordBuilder::build) проверяется соответствие счетов типу транзакции,
+/// а знак суммы приводится к определяемому типом (см. [`TransactionRecord::amount`](crate::TransactionRecord::amount)).
+#[derive(Debug, Default, Clone)]
+pub struct TransactionRecordBuilder {
+    tx_id: Option<u64>,
+    tx_type: Option<TxType>,
+    from_user_id: u64,
+    to_user_id: u64,
+    amount: Option<i64>,
+    timestamp: Option<u64>,
+    status: Option<Status>,
+    description: String,
+}
+
+impl TransactionRecordBuilder {
+    pub fn tx_id(mut self, tx_id: u64) -> Self {
+        self.tx_id = Some(tx_id);
+        self
+    }
+
+    pub fn tx_type(mut self, tx_type: TxType) -> Self {
+        self.tx_type = Some(tx_type);
+        self
+    }
+
+    pub fn from_user_id(mut self, from_user_id: u64) -> Self {
+        self.from_user_id = from_user_id;
+        self
+    }
+
+    pub fn to_user_id(mut self, to_user_id: u64) -> Self {
+        self.to_user_id = to_user_id;
+        self
+    }
+
+    pub fn amount(mut self, amount: i64) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Проверяет поля и создаёт запись
+    pub fn build(self) -> Result<TransactionRecord, RecordBuildError> {
+        let tx_id = self.tx_id.ok_or(RecordBuildError::MissingField("tx_id"))?;
+        let tx_type = self
+            .tx_type
+            .ok_or(RecordBuildError::MissingField("tx_type"))?;
+        let amount = self
+            .amount
+            .ok_or(RecordBuildError::MissingField("amount"))?;
+        let timestamp = self
+            .timestamp
+            .ok_or(RecordBuildError::MissingField("timestamp"))?;
+        let status = self
+            .status
+            .ok_or(RecordBuildError::MissingField("status"))?;
+
+        check_user_ids(tx_type, self.from_user_id, self.to_user_id)?;
+        check_amount_sign(tx_type, amount)
+            .map_err(|reason| RecordBuildError::InvalidAmount { amount, reason })?;
+
+        Ok(TransactionRecord {
+            tx_id,
+            tx_type,
+            from_user_id: self.from_user_id,
+            to_user_id: self.to_user_id,
+            amount,
+            timestamp,
+            status,
+            description: self.description,
+        })
+    }
+}
+
+/// Проверяет, что счета отправителя и получателя соответствуют типу транзакции
+pub(crate) fn check_user_ids(
+    tx_type: TxType,
+    from_user_id: u64,
+    to_user_id: u64,
+) -> Result<(), RecordBuildError> {
+    match tx_type {
+        TxType::DEPOSIT if from_user_id != 0 => {
+            Err(RecordBuildError::DepositWithSender(from_user_id))
+        }
+        TxType::WITHDRAWAL if to_user_id != 0 => {
+            Err(RecordBuildError::WithdrawalWithReceiver(to_user_id))
+        }
+        TxType::TRANSFER if from_user_id == 0 || to_user_id == 0 => {
+            Err(RecordBuildError::TransferWithoutUsers {
+                from_user_id,
+                to_user_id,
+            })
+        }
+        TxType::TRANSFER if from_user_id == to_user_id => {
+            Err(RecordBuildError::TransferToSelf(from_user_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Проверяет, что знак суммы соответствует типу транзакции: DEPOSIT и TRANSFER неотрицательны,
+/// WITHDRAWAL (списание) неположительна
+pub(crate) fn check_amount_sign(tx_type: TxType, amount: i64) -> Result<(), AmountError> {
+    match tx_type {
+        TxType::DEPOSIT if amount < 0 => Err(AmountError::NegativeDeposit),
+        TxType::TRANSFER if amount < 0 => Err(AmountError::NegativeTransfer),
+        TxType::WITHDRAWAL if amount > 0 => Err(AmountError::PositiveWithdrawal),
+        _ => Ok(()),
+    }
+}
+
+/// Восстанавливает сумму со знаком по величине из текстового формата (CSV, TXT):
+/// сумма WITHDRAWAL становится отрицательной
+pub(crate) fn amount_from_magnitude(tx_type: TxType, magnitude: u128) -> Result<i64, AmountError> {
+    let magnitude = i128::try_from(magnitude).map_err(|_| AmountError::OutOfRange)?;
+    let signed = match tx_type {
+        TxType::WITHDRAWAL => -magnitude,
+        TxType::DEPOSIT | TxType::TRANSFER => magnitude,
+    };
+    i64::try_from(signed).map_err(|_| AmountError::OutOfRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_valid_record() {
+        let record = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::TRANSFER)
+            .from_user_id(10)
+            .to_user_id(20)
+            .amount(500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .description("Payment")
+            .build()
+            .unwrap();
+
+        assert_eq!(record.tx_id(), 1);
+        assert_eq!(record.tx_type(), TxType::TRANSFER);
+        assert_eq!(record.from_user_id(), 10);
+        assert_eq!(record.to_user_id(), 20);
+        assert_eq!(record.amount(), 500);
+        assert_eq!(record.timestamp(), 1633036860000);
+        assert_eq!(record.status(), Status::SUCCESS);
+        assert_eq!(record.description(), "Payment");
+    }
+
+    #[test]
+    fn test_deposit_with_sender_rejected() {
+        let result = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::DEPOSIT)
+            .from_user_id(10)
+            .to_user_id(20)
+            .amount(500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+
+        assert_eq!(result, Err(RecordBuildError::DepositWithSender(10)));
+    }
+
+    #[test]
+    fn test_withdrawal_with_receiver_rejected() {
+        let result = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::WITHDRAWAL)
+            .from_user_id(10)
+            .to_user_id(20)
+            .amount(500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+
+        assert_eq!(result, Err(RecordBuildError::WithdrawalWithReceiver(20)));
+    }
+
+    #[test]
+    fn test_transfer_to_self_rejected() {
+        let result = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::TRANSFER)
+            .from_user_id(10)
+            .to_user_id(10)
+            .amount(500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+
+        assert_eq!(result, Err(RecordBuildError::TransferToSelf(10)));
+    }
+
+    #[test]
+    fn test_amount_sign_follows_tx_type() {
+        let withdrawal = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::WITHDRAWAL)
+            .from_user_id(10)
+            .amount(-500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build()
+            .unwrap();
+        assert_eq!(withdrawal.amount(), -500);
+        assert_eq!(withdrawal.amount_abs(), 500);
+
+        let deposit = TransactionRecord::builder()
+            .tx_id(2)
+            .tx_type(TxType::DEPOSIT)
+            .to_user_id(10)
+            .amount(-500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+        assert_eq!(
+            deposit,
+            Err(RecordBuildError::InvalidAmount {
+                amount: -500,
+                reason: AmountError::NegativeDeposit
+            })
+        );
+
+        let transfer = TransactionRecord::builder()
+            .tx_id(3)
+            .tx_type(TxType::TRANSFER)
+            .from_user_id(10)
+            .to_user_id(20)
+            .amount(-500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+        assert_eq!(
+            transfer,
+            Err(RecordBuildError::InvalidAmount {
+                amount: -500,
+                reason: AmountError::NegativeTransfer
+            })
+        );
+
+        let withdrawal = TransactionRecord::builder()
+            .tx_id(4)
+            .tx_type(TxType::WITHDRAWAL)
+            .from_user_id(10)
+            .amount(500)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+        assert_eq!(
+            withdrawal,
+            Err(RecordBuildError::InvalidAmount {
+                amount: 500,
+                reason: AmountError::PositiveWithdrawal
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_field_rejected() {
+        let result = TransactionRecord::builder()
+            .tx_id(1)
+            .tx_type(TxType::DEPOSIT)
+            .to_user_id(20)
+            .timestamp(1633036860000)
+            .status(Status::SUCCESS)
+            .build();
+
+        assert_eq!(result, Err(RecordBuildError::MissingField("amount")));
+    }
+}
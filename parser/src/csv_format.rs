@@ -1,6 +1,8 @@
-use crate::{RecordParser, TransactionRecord};
-use csv::{QuoteStyle, ReaderBuilder, WriterBuilder};
-use std::io::{Read, Result, Write};
+use crate::error::{FieldError, ParseError, ParseResult, Position};
+use crate::fields::{FIELD_NAMES, FieldContext, record_from_fields};
+use crate::{Format, RecordParser, RecordReader, RecordWriter, TransactionRecord};
+use csv_core::{QuoteStyle, ReadRecordResult, Terminator, WriteResult};
+use std::io::{BufRead, BufReader, BufWriter, Read, Result, Write};
 
 /// Коллекция банковских записей, полученная из CSV-файла формата YP Bank.
 ///
@@ -19,61 +21,399 @@ impl YPBankCsvRecords {
 }
 
 impl RecordParser for YPBankCsvRecords {
-    fn from_read<R: Read>(r: &mut R) -> Result<Self>
+    fn from_read<R: Read>(r: &mut R) -> ParseResult<Self>
     where
         Self: Sized,
     {
-        let mut rdr = ReaderBuilder::new().has_headers(true).from_reader(r);
-
-        let mut records = Vec::new();
-        for result in rdr.deserialize() {
-            let record: TransactionRecord = result
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
-            records.push(record);
-        }
+        let records = YPBankCsvReader::new(r).collect::<ParseResult<Vec<_>>>()?;
         Ok(YPBankCsvRecords { records })
     }
 
     fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<()> {
-        let mut wtr = WriterBuilder::new()
-            .has_headers(false)
-            .quote_style(QuoteStyle::Never)
-            .from_writer(writer);
-
-        wtr.write_record([
-            "TX_ID",
-            "TX_TYPE",
-            "FROM_USER_ID",
-            "TO_USER_ID",
-            "AMOUNT",
-            "TIMESTAMP",
-            "STATUS",
-            "DESCRIPTION",
-        ])?;
-
+        let mut csv_writer = YPBankCsvWriter::new(writer);
         for record in &self.records {
-            let description = format!("\"{}\"", record.description);
-
-            wtr.write_record(&[
-                record.tx_id.to_string(),
-                record.tx_type.to_string(),
-                record.from_user_id.to_string(),
-                record.to_user_id.to_string(),
-                record.amount.to_string(),
-                record.timestamp.to_string(),
-                record.status.to_string(),
-                description,
-            ])?;
-        }
-        wtr.flush()?;
+            csv_writer.write_record(record)?;
+        }
+        csv_writer.finish()
+    }
+}
+
+/// Потоковый читатель CSV-файла формата YP Bank.
+///
+/// Читает записи по одной через [`RecordReader`](crate::RecordReader), не загружая весь файл в память.
+/// Колонки сопоставляются по именам из строки заголовка.
+/// Ошибка в одной строке не мешает чтению следующих строк, ошибка заголовка прекращает чтение.
+///
+/// Поля разбираются по RFC 4180: поле в кавычках может содержать запятые, переводы строк
+/// и удвоенные кавычки `""`. Также принимаются файлы, записанные прежними версиями библиотеки:
+/// одиночные кавычки внутри описания в кавычках и незакавыченное описание с запятыми
+/// в последней колонке.
+pub struct YPBankCsvReader<R: Read> {
+    rows: CsvRows<R>,
+    // Индексы колонок в порядке FIELD_NAMES и число колонок заголовка, определяются при первом чтении
+    columns: Option<([usize; FIELD_NAMES.len()], usize)>,
+    // Исходная строка последней отклонённой записи
+    rejected: Vec<u8>,
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    finished: bool,
+}
+
+impl<R: Read> YPBankCsvReader<R> {
+    pub fn new(r: R) -> Self {
+        YPBankCsvReader {
+            rows: CsvRows::new(r),
+            columns: None,
+            rejected: Vec::new(),
+            record: 0,
+            finished: false,
+        }
+    }
+
+    fn columns(&mut self) -> ParseResult<([usize; FIELD_NAMES.len()], usize)> {
+        if let Some(columns) = self.columns {
+            return Ok(columns);
+        }
+
+        let header = self.rows.next_row()?.unwrap_or_default();
+        let headers: Vec<&[u8]> = header
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let name = name.trim_ascii();
+                match i {
+                    0 => name.strip_prefix(UTF8_BOM).unwrap_or(name),
+                    _ => name,
+                }
+            })
+            .collect();
+
+        let mut columns = [0; FIELD_NAMES.len()];
+        for (column, field) in columns.iter_mut().zip(FIELD_NAMES) {
+            *column = headers
+                .iter()
+                .position(|header| *header == field.as_bytes())
+                .ok_or(ParseError::InvalidHeader {
+                    format: Format::Csv,
+                    position: Position::Line(header.line.max(1)),
+                    field,
+                })?;
+        }
+        self.columns = Some((columns, headers.len()));
+        Ok((columns, headers.len()))
+    }
+}
+
+impl<R: Read> RecordReader for YPBankCsvReader<R> {
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let (columns, header_len) = self.columns().inspect_err(|_| self.finished = true)?;
+        let Some(mut row) = self.rows.next_row()? else {
+            self.finished = true;
+            return Ok(None);
+        };
+        self.record += 1;
+
+        // Прежние версии записывали описание без экранирования: запятые из последней колонки
+        // описания попадают в лишние поля, которые склеиваются обратно
+        let description = columns[FIELD_NAMES.len() - 1];
+        if row.fields.len() > header_len && description == header_len - 1 {
+            let tail = row.fields.split_off(description);
+            row.fields.push(tail.join(&b','));
+        }
+
+        let ctx = FieldContext {
+            format: Format::Csv,
+            record: self.record,
+            position: Position::Line(row.line),
+        };
+        let result = row_to_record(&ctx, &row.fields, &columns);
+        if result.is_err() {
+            self.rejected = row.raw;
+        }
+        result.map(Some)
+    }
+
+    fn take_rejected(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rejected)
+    }
+}
+
+fn row_to_record(
+    ctx: &FieldContext,
+    row: &[Vec<u8>],
+    columns: &[usize; FIELD_NAMES.len()],
+) -> ParseResult<TransactionRecord> {
+    let mut values = [None; FIELD_NAMES.len()];
+    for ((value, field), column) in values.iter_mut().zip(FIELD_NAMES).zip(columns) {
+        if let Some(bytes) = row.get(*column) {
+            let text = std::str::from_utf8(bytes).map_err(|_| {
+                ctx.invalid(
+                    field,
+                    ctx.position,
+                    &String::from_utf8_lossy(bytes),
+                    FieldError::InvalidUtf8,
+                )
+            })?;
+            *value = Some(text);
+        }
+    }
+
+    record_from_fields(ctx, |field| {
+        let index = FIELD_NAMES.iter().position(|name| *name == field)?;
+        values[index].map(|value| (value, ctx.position))
+    })
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Строка CSV: поля, номер первой физической строки и исходные байты
+#[derive(Default)]
+struct CsvRow {
+    fields: Vec<Vec<u8>>,
+    line: u64,
+    raw: Vec<u8>,
+}
+
+// Разбор CSV на строки по RFC 4180 через csv_core; строки, записанные прежними версиями библиотеки
+// с одиночными кавычками внутри описания, разбираются вручную. Пустые строки пропускаются
+struct CsvRows<R: Read> {
+    reader: BufReader<R>,
+    csv: csv_core::Reader,
+    // Номер последней прочитанной физической строки
+    line: u64,
+}
+
+impl<R: Read> CsvRows<R> {
+    fn new(r: R) -> Self {
+        CsvRows {
+            reader: BufReader::new(r),
+            csv: csv_core::Reader::new(),
+            line: 0,
+        }
+    }
+
+    fn next_row(&mut self) -> Result<Option<CsvRow>> {
+        let mut raw = Vec::new();
+        loop {
+            raw.clear();
+            if self.reader.read_until(b'\n', &mut raw)? == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+            if !raw.trim_ascii().is_empty() {
+                break;
+            }
+        }
+
+        let mut row = CsvRow {
+            line: self.line,
+            ..CsvRow::default()
+        };
+        self.csv.reset();
+        let mut output = vec![0; raw.len()];
+        let mut ends = vec![0; FIELD_NAMES.len() + 1];
+        let (mut nin, mut nout, mut nend) = (0, 0, 0);
+        loop {
+            let (result, i, o, e) =
+                self.csv
+                    .read_record(&raw[nin..], &mut output[nout..], &mut ends[nend..]);
+            (nin, nout, nend) = (nin + i, nout + o, nend + e);
+            match result {
+                ReadRecordResult::Record | ReadRecordResult::End => break,
+                ReadRecordResult::OutputFull => output.resize(output.len() * 2 + 1, 0),
+                ReadRecordResult::OutputEndsFull => ends.resize(ends.len() * 2, 0),
+                // Перевод строки внутри поля в кавычках: поле продолжается на следующей строке.
+                // В конце данных разбор завершается вызовом с пустым входом
+                ReadRecordResult::InputEmpty => {
+                    if self.reader.read_until(b'\n', &mut raw)? > 0 {
+                        self.line += 1;
+                        output.resize(raw.len(), 0);
+                    }
+                }
+            }
+        }
+
+        row.fields = if is_legacy_quoting(&raw) {
+            legacy_fields(&raw)
+        } else {
+            let mut start = 0;
+            ends[..nend]
+                .iter()
+                .map(|&end| {
+                    let field = output[start..end].to_vec();
+                    start = end;
+                    field
+                })
+                .collect()
+        };
+        row.raw = raw;
+        Ok(Some(row))
+    }
+}
+
+// Прежние версии записывали кавычки внутри описания без удвоения. По RFC 4180 кавычка внутри поля
+// всегда соседствует с другой кавычкой, а открывающая и закрывающая стоят на границе поля,
+// поэтому одиночная кавычка в середине поля означает строку прежнего формата
+fn is_legacy_quoting(raw: &[u8]) -> bool {
+    raw.iter().enumerate().any(|(i, &byte)| {
+        byte == b'"'
+            && i > 0
+            && !matches!(raw[i - 1], b'"' | b',')
+            && !matches!(raw.get(i + 1), None | Some(b'"' | b',' | b'\r' | b'\n'))
+    })
+}
+
+// Разбор строки прежнего формата: после одиночной кавычки внутри поля в кавычках
+// удвоенные кавычки больше не считаются экранированием
+fn legacy_fields(raw: &[u8]) -> Vec<Vec<u8>> {
+    let mut fields = Vec::new();
+    let mut field = Vec::new();
+    // None вне кавычек, Some(legacy) внутри поля в кавычках; legacy означает, что в поле
+    // встретилась одиночная кавычка
+    let mut quoted: Option<bool> = None;
+    let mut pos = 0;
+    while let Some(&byte) = raw.get(pos) {
+        let next = raw.get(pos + 1).copied();
+        pos += 1;
+
+        match (quoted, byte) {
+            (Some(legacy), b'"') => match next {
+                None | Some(b',' | b'\r' | b'\n') => quoted = None,
+                Some(b'"') if !legacy => {
+                    field.push(b'"');
+                    pos += 1;
+                }
+                Some(_) => {
+                    quoted = Some(true);
+                    field.push(b'"');
+                }
+            },
+            (Some(_), _) => field.push(byte),
+            (None, b'"') if field.is_empty() => quoted = Some(false),
+            (None, b',') => fields.push(std::mem::take(&mut field)),
+            (None, b'\n') => break,
+            (None, b'\r') if matches!(next, None | Some(b'\n')) => {}
+            (None, _) => field.push(byte),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Потоковый писатель CSV-файла формата YP Bank.
+///
+/// Строка заголовка записывается один раз: перед первой записью
+/// или при вызове [`RecordWriter::finish`](crate::RecordWriter::finish), если записей не было.
+/// Строки завершаются CRLF по RFC 4180. Описание всегда заключается в кавычки, как требует
+/// спецификация, остальные поля — только если они содержат запятую, кавычку или перевод строки.
+pub struct YPBankCsvWriter<W: Write> {
+    writer: BufWriter<W>,
+    fields: csv_core::Writer,
+    description: csv_core::Writer,
+    // Закодированная строка перед записью в writer
+    row: Vec<u8>,
+    header_written: bool,
+}
+
+impl<W: Write> YPBankCsvWriter<W> {
+    pub fn new(w: W) -> Self {
+        let encoder = |quote_style| {
+            csv_core::WriterBuilder::new()
+                .quote_style(quote_style)
+                .terminator(Terminator::CRLF)
+                .build()
+        };
+        YPBankCsvWriter {
+            writer: BufWriter::new(w),
+            fields: encoder(QuoteStyle::Necessary),
+            description: encoder(QuoteStyle::Always),
+            row: Vec::new(),
+            header_written: false,
+        }
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        self.row.clear();
+        for (i, name) in FIELD_NAMES.iter().enumerate() {
+            if i > 0 {
+                encode_end(&mut self.row, |out| self.fields.delimiter(out));
+            }
+            encode_field(&mut self.fields, name.as_bytes(), &mut self.row);
+        }
+        encode_end(&mut self.row, |out| self.fields.terminator(out));
+        self.writer.write_all(&self.row)?;
+        self.header_written = true;
         Ok(())
     }
 }
 
+// Кодирует поле в конец row
+fn encode_field(encoder: &mut csv_core::Writer, mut input: &[u8], row: &mut Vec<u8>) {
+    loop {
+        let start = row.len();
+        // Больше в худшем случае не понадобится: каждая кавычка удваивается, поле берётся в кавычки
+        row.resize(start + 2 * input.len() + 2, 0);
+        let (result, nin, nout) = encoder.field(input, &mut row[start..]);
+        row.truncate(start + nout);
+        input = &input[nin..];
+        if result == WriteResult::InputEmpty {
+            return;
+        }
+    }
+}
+
+// Дописывает в конец row разделитель или конец строки вместе с закрывающей кавычкой поля
+fn encode_end(row: &mut Vec<u8>, encode: impl FnOnce(&mut [u8]) -> (WriteResult, usize)) {
+    let start = row.len();
+    row.resize(start + 4, 0);
+    let (_, nout) = encode(&mut row[start..]);
+    row.truncate(start + nout);
+}
+
+impl<W: Write> RecordWriter for YPBankCsvWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> Result<()> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+
+        self.row.clear();
+        for value in [
+            record.tx_id.to_string(),
+            record.tx_type.to_string(),
+            record.from_user_id.to_string(),
+            record.to_user_id.to_string(),
+            record.amount_abs().to_string(),
+            record.timestamp.to_string(),
+            record.status.to_string(),
+        ] {
+            encode_field(&mut self.fields, value.as_bytes(), &mut self.row);
+            encode_end(&mut self.row, |out| self.fields.delimiter(out));
+        }
+        encode_field(
+            &mut self.description,
+            record.description.as_bytes(),
+            &mut self.row,
+        );
+        encode_end(&mut self.row, |out| self.description.terminator(out));
+        self.writer.write_all(&self.row)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.header_written {
+            self.write_header()?;
+        }
+        self.writer.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Status, TxType};
+    use crate::{AmountError, Status, TxType, YPBankBinRecords};
     use std::io::Cursor;
 
     #[test]
@@ -99,6 +439,141 @@ mod tests {
         assert_eq!(test_csv_records, buff_record);
     }
 
+    #[test]
+    fn test_reader_yields_records_one_by_one() {
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"first\"
+2,WITHDRAWAL,10,0,50,1633036920000,PENDING,\"second\"
+";
+        let mut reader = YPBankCsvReader::new(Cursor::new(data));
+        assert_eq!(reader.read_record().unwrap().unwrap().tx_id, 1);
+        assert_eq!(reader.read_record().unwrap().unwrap().tx_id, 2);
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_writer_emits_header_once() {
+        let record = TransactionRecord {
+            tx_type: TxType::DEPOSIT,
+            status: Status::SUCCESS,
+            tx_id: 1,
+            from_user_id: 0,
+            to_user_id: 10,
+            amount: 100,
+            timestamp: 1633036860000,
+            description: "first".to_string(),
+        };
+
+        let mut buffer = Vec::new();
+        let mut writer = YPBankCsvWriter::new(&mut buffer);
+        writer.write_record(&record).unwrap();
+        writer.write_record(&record).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let output = String::from_utf8(buffer).unwrap();
+        assert_eq!(output.matches("TX_ID,TX_TYPE").count(), 1);
+        assert_eq!(output.lines().count(), 3);
+
+        let mut empty = Vec::new();
+        YPBankCsvWriter::new(&mut empty).finish().unwrap();
+        assert!(String::from_utf8(empty).unwrap().starts_with("TX_ID,"));
+    }
+
+    #[test]
+    fn test_amount_sign_conversion() {
+        let mut records = YPBankCsvRecords::new(vec![TransactionRecord {
+            tx_type: TxType::WITHDRAWAL,
+            status: Status::SUCCESS,
+            tx_id: 1,
+            from_user_id: 10,
+            to_user_id: 0,
+            amount: -250,
+            timestamp: 1633036860000,
+            description: "debit".to_string(),
+        }]);
+
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains(",250,"));
+
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,WITHDRAWAL,10,0,-250,1633036860000,SUCCESS,\"debit\"
+";
+        let err = YPBankCsvRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidAmount {
+                reason: AmountError::NegativeInText,
+                ..
+            }
+        ));
+
+        // Величина списания до 2^63 допустима, больше — нет; у остальных типов предел i64::MAX
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,WITHDRAWAL,10,0,9223372036854775808,1633036860000,SUCCESS,\"debit\"
+2,DEPOSIT,0,10,9223372036854775808,1633036860000,SUCCESS,\"credit\"
+3,WITHDRAWAL,10,0,18446744073709551616,1633036860000,SUCCESS,\"debit\"
+";
+        let mut reader = YPBankCsvReader::new(Cursor::new(data));
+        assert_eq!(reader.read_record().unwrap().unwrap().amount(), i64::MIN);
+        for _ in 0..2 {
+            assert!(matches!(
+                reader.read_record(),
+                Err(ParseError::InvalidAmount {
+                    reason: AmountError::OutOfRange,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_bin_csv_bin_round_trip_keeps_sign() {
+        let records: Vec<TransactionRecord> = [
+            (TxType::WITHDRAWAL, 10, 0, -300),
+            (TxType::DEPOSIT, 0, 10, 300),
+            (TxType::TRANSFER, 10, 20, 300),
+        ]
+        .into_iter()
+        .map(|(tx_type, from_user_id, to_user_id, amount)| {
+            TransactionRecord::builder()
+                .tx_id(1)
+                .tx_type(tx_type)
+                .from_user_id(from_user_id)
+                .to_user_id(to_user_id)
+                .amount(amount)
+                .timestamp(1633036860000)
+                .status(Status::SUCCESS)
+                .build()
+                .unwrap()
+        })
+        .collect();
+        let amounts: Vec<i64> = records.iter().map(|r| r.amount()).collect();
+        assert_eq!(amounts, vec![-300, 300, 300]);
+
+        let mut bin = Vec::new();
+        YPBankBinRecords::new(records.clone())
+            .write_to(&mut bin)
+            .unwrap();
+        let from_bin = YPBankBinRecords::from_read(&mut Cursor::new(bin)).unwrap();
+        let mut csv = Vec::new();
+        YPBankCsvRecords::new(from_bin.records)
+            .write_to(&mut csv)
+            .unwrap();
+        let from_csv = YPBankCsvRecords::from_read(&mut Cursor::new(csv)).unwrap();
+        let mut bin = Vec::new();
+        YPBankBinRecords::new(from_csv.records)
+            .write_to(&mut bin)
+            .unwrap();
+        let round_trip = YPBankBinRecords::from_read(&mut Cursor::new(bin)).unwrap();
+        assert_eq!(round_trip.records, records);
+    }
+
     #[test]
     fn test_invalid_tx_type_value() {
         let data = "\
@@ -110,9 +585,17 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let result = YPBankCsvRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
-        // Сообщение об ошибке будет содержать информацию о невозможности десериализации
-        assert!(err.to_string().contains("TX_TYPE") || err.to_string().contains("deposit"));
+        assert!(matches!(
+            err,
+            ParseError::InvalidField {
+                format: Format::Csv,
+                record: 1,
+                position: Position::Line(2),
+                field: "TX_TYPE",
+                ref value,
+                reason: FieldError::UnknownValue,
+            } if value == "deposit"
+        ));
     }
 
     #[test]
@@ -126,7 +609,14 @@ TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
         let result = YPBankCsvRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(matches!(
+            err,
+            ParseError::MissingField {
+                record: 1,
+                field: "DESCRIPTION",
+                ..
+            }
+        ));
     }
 
     #[test]
@@ -140,6 +630,196 @@ not_a_number,DEPOSIT,0,9223372036854775807,100,1633036860,SUCCESS,\"test\"
         let result = YPBankCsvRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(matches!(
+            err,
+            ParseError::InvalidField {
+                field: "TX_ID",
+                ref value,
+                reason: FieldError::InvalidInteger(_),
+                ..
+            } if value == "not_a_number"
+        ));
+    }
+
+    #[test]
+    fn test_missing_header_column() {
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,1633036860,SUCCESS,\"test\"
+";
+        let err = YPBankCsvRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidHeader {
+                field: "AMOUNT",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_lenient_skips_invalid_rows() {
+        let data = b"\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"first\"
+2,DEPOSITX,0,10,100,1633036860000,SUCCESS,\"second\"
+3,DEPOSIT,0,10,\xFF,1633036860000,SUCCESS,\"third\"
+4,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"fourth\"
+";
+
+        let (records, issues) = crate::parse_lenient(Format::Csv, Cursor::new(data)).unwrap();
+        let ids: Vec<u64> = records.iter().map(|r| r.tx_id).collect();
+        assert_eq!(ids, vec![1, 4]);
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(
+            issues[0].error,
+            ParseError::InvalidField {
+                record: 2,
+                field: "TX_TYPE",
+                ..
+            }
+        ));
+        assert_eq!(
+            issues[0].raw,
+            b"2,DEPOSITX,0,10,100,1633036860000,SUCCESS,\"second\"\n"
+        );
+        assert!(matches!(
+            issues[1].error,
+            ParseError::InvalidField {
+                record: 3,
+                position: Position::Line(4),
+                field: "AMOUNT",
+                ..
+            }
+        ));
+    }
+
+    fn record_with_description(description: &str) -> TransactionRecord {
+        TransactionRecord {
+            tx_type: TxType::DEPOSIT,
+            status: Status::SUCCESS,
+            tx_id: 1,
+            from_user_id: 0,
+            to_user_id: 10,
+            amount: 100,
+            timestamp: 1633036860000,
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_description_quoting() {
+        let mut records =
+            YPBankCsvRecords::new(vec![record_with_description("Say \"hi\", then\r\nleave")]);
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.ends_with(",\"Say \"\"hi\"\", then\r\nleave\"\r\n"));
+
+        let read = YPBankCsvRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(records, read);
+    }
+
+    #[test]
+    fn test_writer_output() {
+        let mut records =
+            YPBankCsvRecords::new(vec![record_with_description("Gift, from \"mom\"")]);
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION\r\n\
+             1,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"Gift, from \"\"mom\"\"\"\r\n"
+        );
+
+        let mut buffer = Vec::new();
+        YPBankCsvRecords::new(vec![record_with_description("")])
+            .write_to(&mut buffer)
+            .unwrap();
+        assert!(buffer.ends_with(b",SUCCESS,\"\"\r\n"));
+    }
+
+    #[test]
+    fn test_writer_buffers_rows() {
+        // Считает обращения к нижележащему писателю
+        struct CountingWriter(usize);
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> Result<usize> {
+                self.0 += 1;
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut sink = CountingWriter(0);
+        let mut writer = YPBankCsvWriter::new(&mut sink);
+        for _ in 0..100 {
+            writer
+                .write_record(&record_with_description("row"))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+        assert_eq!(sink.0, 1);
+    }
+
+    #[test]
+    fn test_legacy_quoting_detection() {
+        assert!(!is_legacy_quoting(b"1,\"a\"\"b\",\"\"\"x\"\r\n"));
+        assert!(!is_legacy_quoting(b"1,\"two\nlines\"\"\"\n"));
+        assert!(is_legacy_quoting(b"1,\"Say \"hi\"\"\n"));
+    }
+
+    #[test]
+    fn test_legacy_quoting_accepted() {
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"Say \"hi\"\"
+2,DEPOSIT,0,10,100,1633036860000,SUCCESS,Gift, from mom
+3,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"Record number 3\"
+";
+        let records = YPBankCsvRecords::from_read(&mut Cursor::new(data)).unwrap();
+        let descriptions: Vec<&str> = records.records.iter().map(|r| r.description()).collect();
+        assert_eq!(
+            descriptions,
+            vec!["Say \"hi\"", "Gift, from mom", "Record number 3"]
+        );
+    }
+
+    #[test]
+    fn test_multiline_record_position() {
+        let data = "\
+TX_ID,TX_TYPE,FROM_USER_ID,TO_USER_ID,AMOUNT,TIMESTAMP,STATUS,DESCRIPTION
+1,DEPOSIT,0,10,100,1633036860000,SUCCESS,\"two
+lines\"
+2,DEPOSITX,0,10,100,1633036860000,SUCCESS,\"bad\"
+";
+        let mut reader = YPBankCsvReader::new(Cursor::new(data));
+        assert_eq!(
+            reader.read_record().unwrap().unwrap().description(),
+            "two\nlines"
+        );
+        assert!(matches!(
+            reader.read_record().unwrap_err(),
+            ParseError::InvalidField {
+                position: Position::Line(4),
+                ..
+            }
+        ));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_description_round_trip(description in "\\PC*|[\",\r\n ]{0,8}") {
+            let mut records = YPBankCsvRecords::new(vec![record_with_description(&description)]);
+            let mut buffer = Vec::new();
+            records.write_to(&mut buffer).unwrap();
+
+            let read = YPBankCsvRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+            proptest::prop_assert_eq!(records, read);
+        }
     }
 }
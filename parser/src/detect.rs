@@ -0,0 +1,100 @@
+use crate::Format;
+use crate::bin_format::{FILE_MAGIC, MAGIC};
+use crate::error::DetectFormatError;
+use crate::fields::FIELD_NAMES;
+
+/// Определяет формат данных по их началу.
+///
+/// Достаточно передать первые несколько килобайт файла: BIN распознаётся по `MAGIC` (`YPBN`)
+/// или заголовку файла версии 2 (`YPBF`) в начале данных, CSV — по строке заголовка
+/// с колонкой `TX_ID`, TXT — по первой значимой строке вида `KEY: value` с именем поля из спецификации,
+/// JSON — по открывающей скобке массива `[`, NDJSON — по открывающей скобке объекта `{`.
+/// Если данные подходят под несколько форматов, возвращается [`DetectFormatError::Ambiguous`].
+///
+/// Текстовые форматы распознаются по первой строке, которая не пуста и не является комментарием `#`,
+/// поэтому `data` должна содержать её целиком: если файл начинается с длинного блока комментариев,
+/// начало нужно читать дальше первых килобайт.
+pub fn detect_format(data: &[u8]) -> Result<Format, DetectFormatError> {
+    let mut candidates = Vec::new();
+
+    if data.starts_with(&MAGIC.to_be_bytes()) || data.starts_with(&FILE_MAGIC.to_be_bytes()) {
+        candidates.push(Format::Bin);
+    }
+
+    let text = String::from_utf8_lossy(data);
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    if looks_like_csv(text) {
+        candidates.push(Format::Csv);
+    }
+    if looks_like_txt(text) {
+        candidates.push(Format::Txt);
+    }
+    match text.trim_start().chars().next() {
+        Some('[') => candidates.push(Format::Json),
+        Some('{') => candidates.push(Format::Ndjson),
+        _ => {}
+    }
+
+    match candidates.as_slice() {
+        [] => Err(DetectFormatError::Unknown),
+        [format] => Ok(*format),
+        _ => Err(DetectFormatError::Ambiguous(candidates)),
+    }
+}
+
+// Первая строка CSV-файла — заголовок, среди колонок которого есть TX_ID
+fn looks_like_csv(text: &str) -> bool {
+    let header = text.lines().next().unwrap_or_default();
+    header.contains(',') && header.split(',').any(|column| column.trim() == "TX_ID")
+}
+
+// Первая строка TXT-файла, не являющаяся комментарием, — поле записи вида `KEY: value`
+fn looks_like_txt(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .and_then(|line| line.split_once(": "))
+        .is_some_and(|(key, _)| FIELD_NAMES.contains(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_example_files() {
+        let bin = include_bytes!("../../test_files/records_example.bin");
+        let csv = include_bytes!("../../test_files/records_example.csv");
+        let txt = include_bytes!("../../test_files/records_example.txt");
+
+        assert_eq!(detect_format(bin), Ok(Format::Bin));
+        assert_eq!(detect_format(csv), Ok(Format::Csv));
+        assert_eq!(detect_format(txt), Ok(Format::Txt));
+        assert_eq!(detect_format(b"YPBF\x00\x02"), Ok(Format::Bin));
+    }
+
+    #[test]
+    fn test_detect_json_and_ndjson() {
+        assert_eq!(detect_format(b"\n  [\n  {\"TX_ID\":1}"), Ok(Format::Json));
+        assert_eq!(detect_format(b"{\"TX_ID\":1}\n"), Ok(Format::Ndjson));
+    }
+
+    #[test]
+    fn test_detect_txt_after_comments() {
+        let data = b"# Record 1 DEPOSIT\n\nTX_TYPE: DEPOSIT\n";
+        assert_eq!(detect_format(data), Ok(Format::Txt));
+    }
+
+    #[test]
+    fn test_detect_unknown_and_ambiguous() {
+        assert_eq!(detect_format(b""), Err(DetectFormatError::Unknown));
+        assert_eq!(
+            detect_format(b"hello, world\n"),
+            Err(DetectFormatError::Unknown)
+        );
+        assert_eq!(
+            detect_format(b"TX_ID: 1,TX_ID,TX_TYPE\n"),
+            Err(DetectFormatError::Ambiguous(vec![Format::Csv, Format::Txt]))
+        );
+    }
+}
@@ -1,34 +1,307 @@
-use crate::{Status, TxType};
+use crate::Format;
+use derive_more::Display;
 use thiserror::Error;
 
+/// Позиция записи в исходных данных
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Position {
+    /// Номер строки (с 1) для текстовых форматов
+    #[display("line {_0}")]
+    Line(u64),
+    /// Смещение от начала данных в байтах для бинарного формата
+    #[display("offset {_0}")]
+    Offset(u64),
+    /// Номер строки (с 1) внутри многострочной записи TXT и номер строки, с которой запись начинается
+    #[display("line {line} (record starts at line {start})")]
+    LineInRecord { start: u64, line: u64 },
+}
+
+/// Ошибка разбора записей транзакций.
+///
+/// Каждый вариант, относящийся к конкретной записи, содержит формат, порядковый номер записи (с 1)
+/// и её позицию в исходных данных, поэтому вызывающий код может сопоставлять варианты,
+/// не разбирая текст сообщения.
 #[derive(Error, Debug)]
-pub(crate) enum BinToTransError {
-    #[error("Invalid transaction type value: {0}")]
-    InvalidTxType(u8),
-    #[error("Invalid transaction status value: {0}")]
-    InvalidStatus(u8),
-    #[error("Description length mismatch: expected {expected}, actual {actual}")]
-    DescriptionLengthMismatch { expected: u32, actual: usize },
-}
-
-impl From<BinToTransError> for std::io::Error {
-    fn from(e: BinToTransError) -> Self {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+#[non_exhaustive]
+pub enum ParseError {
+    /// Ошибка ввода-вывода источника
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// В записи отсутствует обязательное поле
+    #[error("{format} record {record} at {position}: missing field {field}")]
+    MissingField {
+        format: Format,
+        record: usize,
+        position: Position,
+        field: &'static str,
+    },
+
+    /// Значение поля не удалось разобрать или оно недопустимо
+    #[error(
+        "{format} record {record} at {position}: invalid value {value:?} for field {field}: {reason}"
+    )]
+    InvalidField {
+        format: Format,
+        record: usize,
+        position: Position,
+        field: &'static str,
+        value: String,
+        reason: FieldError,
+    },
+
+    /// Значение `AMOUNT` является числом, но недопустимо для формата или типа транзакции
+    #[error("{format} record {record} at {position}: invalid AMOUNT {value:?}: {reason}")]
+    InvalidAmount {
+        format: Format,
+        record: usize,
+        position: Position,
+        value: String,
+        reason: AmountError,
+    },
+
+    /// Строка TXT-записи не соответствует виду `KEY: value`
+    #[error("{format} record {record} at {position}: malformed line {line:?}")]
+    MalformedLine {
+        format: Format,
+        record: usize,
+        position: Position,
+        line: String,
+    },
+
+    /// Поле TXT-записи встречается в блоке повторно
+    #[error("{format} record {record} at {position}: duplicate field {field} in line {line:?}")]
+    DuplicateField {
+        format: Format,
+        record: usize,
+        position: Position,
+        field: &'static str,
+        line: String,
+    },
+
+    /// Ключ TXT-записи не является полем спецификации
+    #[error("{format} record {record} at {position}: unknown field {field:?} in line {line:?}")]
+    UnknownField {
+        format: Format,
+        record: usize,
+        position: Position,
+        field: String,
+        line: String,
+    },
+
+    /// Заголовок CSV-файла не содержит обязательной колонки
+    #[error("{format} header at {position}: missing column {field}")]
+    InvalidHeader {
+        format: Format,
+        position: Position,
+        field: &'static str,
+    },
+
+    /// Данные JSON-файла синтаксически некорректны, поэтому границы записей неизвестны
+    #[error("{format} data at {position}: {message}")]
+    Syntax {
+        format: Format,
+        position: Position,
+        message: String,
+    },
+
+    /// JSON-объект не удалось преобразовать в запись
+    #[error("{format} record {record} at {position}: {message}")]
+    InvalidRecord {
+        format: Format,
+        record: usize,
+        position: Position,
+        message: String,
+    },
+
+    /// Заголовок BIN-записи начинается не с `MAGIC`
+    #[error("bin record {record} at offset {offset}: invalid magic 0x{magic:X}")]
+    InvalidMagic {
+        record: usize,
+        offset: u64,
+        magic: u32,
+    },
+
+    /// Данные BIN-записи закончились раньше, чем указано в заголовке
+    #[error(
+        "bin record {record} at offset {offset}: not enough bytes for {part}: need {expected}, have {actual}"
+    )]
+    Truncated {
+        record: usize,
+        offset: u64,
+        part: &'static str,
+        expected: u64,
+        actual: u64,
+    },
+
+    /// `RECORD_SIZE` BIN-записи превышает [`MAX_RECORD_SIZE`](crate::MAX_RECORD_SIZE)
+    #[error("bin record {record} at offset {offset}: record size {size} exceeds maximum {max}")]
+    RecordTooLarge {
+        record: usize,
+        offset: u64,
+        size: u32,
+        max: u32,
+    },
+
+    /// CRC32 тела BIN-записи не совпадает с контрольной суммой, записанной после него
+    #[error(
+        "bin record {record} (TX_ID {tx_id}) at offset {offset}: checksum mismatch: stored 0x{expected:08X}, computed 0x{actual:08X}"
+    )]
+    RecordChecksumMismatch {
+        record: usize,
+        offset: u64,
+        tx_id: u64,
+        expected: u32,
+        actual: u32,
+    },
+
+    /// Заголовок BIN-файла версии 2 повреждён или имеет неподдерживаемую версию
+    #[error("bin file header: {reason}")]
+    InvalidFileHeader { reason: FileHeaderError },
+
+    /// Число записей в BIN-файле версии 2 не совпадает с указанным в заголовке файла
+    #[error("bin file: header declares {expected} records, found {actual}")]
+    RecordCountMismatch { expected: u64, actual: u64 },
+
+    /// Контрольная сумма записей BIN-файла версии 2 не совпадает с указанной в заголовке файла
+    #[error(
+        "bin file body: checksum mismatch: header has 0x{expected:08X}, data has 0x{actual:08X}"
+    )]
+    BodyChecksumMismatch { expected: u32, actual: u32 },
+
+    /// Файл индекса BIN-файла повреждён
+    #[error("bin index: {reason}")]
+    InvalidIndex { reason: IndexError },
+
+    /// Запись по смещению из индекса имеет другой `TX_ID`: индекс не соответствует данным
+    #[error("bin index: expected TX_ID {tx_id} at offset {offset}, found TX_ID {found}")]
+    IndexMismatch { tx_id: u64, offset: u64, found: u64 },
+}
+
+impl From<ParseError> for std::io::Error {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Io(e) => e,
+            other => std::io::Error::new(std::io::ErrorKind::InvalidData, other),
+        }
     }
 }
 
-#[derive(Error, Debug)]
-pub(crate) enum TransToBinError {
-    #[allow(dead_code)]
-    #[error("Invalid transaction type: {0:?}")]
-    UnsupportedTxType(TxType),
-    #[allow(dead_code)]
-    #[error("Invalid status value: {0:?}")]
-    UnsupportedStatus(Status),
-}
-
-impl From<TransToBinError> for std::io::Error {
-    fn from(e: TransToBinError) -> Self {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+/// Результат разбора записей
+pub type ParseResult<T> = std::result::Result<T, ParseError>;
+
+/// Запись, пропущенная при разборе в нестрогом режиме
+#[derive(Debug)]
+pub struct ParseIssue {
+    /// Ошибка разбора записи
+    pub error: ParseError,
+    /// Исходные данные записи в том виде, в котором они были прочитаны
+    pub raw: Vec<u8>,
+}
+
+/// Ошибка построения [`TransactionRecord`](crate::TransactionRecord) через
+/// [`TransactionRecordBuilder`](crate::TransactionRecordBuilder)
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RecordBuildError {
+    #[error("Missing required field: {0}")]
+    MissingField(&'static str),
+    #[error("DEPOSIT must have from_user_id = 0, got {0}")]
+    DepositWithSender(u64),
+    #[error("WITHDRAWAL must have to_user_id = 0, got {0}")]
+    WithdrawalWithReceiver(u64),
+    #[error(
+        "TRANSFER requires non-zero from_user_id and to_user_id, got {from_user_id} -> {to_user_id}"
+    )]
+    TransferWithoutUsers { from_user_id: u64, to_user_id: u64 },
+    #[error("TRANSFER requires distinct from_user_id and to_user_id, got {0} for both")]
+    TransferToSelf(u64),
+    #[error("Invalid amount {amount}: {reason}")]
+    InvalidAmount { amount: i64, reason: AmountError },
+}
+
+/// Причина, по которой значение поля не удалось разобрать
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// Значение не является целым числом или не помещается в тип поля
+    #[error("invalid integer: {0}")]
+    InvalidInteger(std::num::ParseIntError),
+    /// Значение не входит в допустимые значения поля (`TX_TYPE`, `STATUS`)
+    #[error("unknown value")]
+    UnknownValue,
+    /// Значение не является корректной строкой UTF-8
+    #[error("value is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+impl From<std::num::ParseIntError> for FieldError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        FieldError::InvalidInteger(e)
+    }
+}
+
+impl From<strum::ParseError> for FieldError {
+    fn from(_: strum::ParseError) -> Self {
+        FieldError::UnknownValue
     }
 }
+
+/// Причина, по которой заголовок BIN-файла версии 2 недопустим
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileHeaderError {
+    /// Версия формата, отличная от 2
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u16),
+    /// Установлены неизвестные флаги
+    #[error("unsupported flags 0x{0:04X}")]
+    UnsupportedFlags(u16),
+    /// Данные закончились раньше заголовка
+    #[error("not enough bytes: need {expected}, have {actual}")]
+    Truncated { expected: usize, actual: usize },
+}
+
+/// Причина, по которой файл индекса BIN-файла недопустим
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexError {
+    /// Файл не начинается с `INDEX_MAGIC`
+    #[error("invalid magic 0x{0:X}")]
+    InvalidMagic(u32),
+    /// Файл закончился раньше, чем указано в его заголовке
+    #[error("unexpected end of file")]
+    Truncated,
+    /// Смещение записи выходит за конец данных
+    #[error("offset {offset} of TX_ID {tx_id} is beyond end of data")]
+    OffsetBeyondData { tx_id: u64, offset: u64 },
+}
+
+/// Причина, по которой числовое значение `AMOUNT` недопустимо
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    /// CSV и TXT хранят только величину суммы, знак определяется типом транзакции
+    #[error("amount must be non-negative in text formats")]
+    NegativeInText,
+    /// Зачисление не может быть отрицательным
+    #[error("DEPOSIT amount must not be negative")]
+    NegativeDeposit,
+    /// Перевод не может быть отрицательным
+    #[error("TRANSFER amount must not be negative")]
+    NegativeTransfer,
+    /// Списание хранится отрицательной суммой
+    #[error("WITHDRAWAL amount must not be positive")]
+    PositiveWithdrawal,
+    /// Величина суммы из текстового формата со знаком, определяемым типом транзакции, не помещается в i64:
+    /// величина больше 9223372036854775807 (9223372036854775808 для WITHDRAWAL)
+    #[error("amount does not fit into a signed 64-bit value")]
+    OutOfRange,
+}
+
+/// Ошибка автоопределения формата по содержимому данных
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DetectFormatError {
+    #[error("unable to detect format: data does not look like bin, csv, txt, json or ndjson")]
+    Unknown,
+    #[error(
+        "ambiguous format: data looks like {}",
+        .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(" and ")
+    )]
+    Ambiguous(Vec<Format>),
+}
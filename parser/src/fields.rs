@@ -0,0 +1,119 @@
+use crate::builder::amount_from_magnitude;
+use crate::error::{AmountError, FieldError, ParseError, ParseResult, Position};
+use crate::{Format, Status, TransactionRecord, TxType};
+use std::str::FromStr;
+
+/// Имена полей записи в порядке спецификации
+pub(crate) const FIELD_NAMES: [&str; 8] = [
+    "TX_ID",
+    "TX_TYPE",
+    "FROM_USER_ID",
+    "TO_USER_ID",
+    "AMOUNT",
+    "TIMESTAMP",
+    "STATUS",
+    "DESCRIPTION",
+];
+
+// Контекст записи текстового формата, используемый при формировании ошибок
+pub(crate) struct FieldContext {
+    pub(crate) format: Format,
+    pub(crate) record: usize,
+    pub(crate) position: Position,
+}
+
+impl FieldContext {
+    pub(crate) fn missing(&self, field: &'static str) -> ParseError {
+        ParseError::MissingField {
+            format: self.format,
+            record: self.record,
+            position: self.position,
+            field,
+        }
+    }
+
+    pub(crate) fn invalid(
+        &self,
+        field: &'static str,
+        position: Position,
+        value: &str,
+        reason: FieldError,
+    ) -> ParseError {
+        ParseError::InvalidField {
+            format: self.format,
+            record: self.record,
+            position,
+            field,
+            value: value.to_string(),
+            reason,
+        }
+    }
+
+    pub(crate) fn invalid_amount(
+        &self,
+        position: Position,
+        value: &str,
+        reason: AmountError,
+    ) -> ParseError {
+        ParseError::InvalidAmount {
+            format: self.format,
+            record: self.record,
+            position,
+            value: value.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Собирает запись из строковых значений полей текстового формата (CSV, TXT).
+///
+/// `lookup` возвращает значение поля и его позицию либо `None`, если поле отсутствует.
+pub(crate) fn record_from_fields<'a>(
+    ctx: &FieldContext,
+    mut lookup: impl FnMut(&'static str) -> Option<(&'a str, Position)>,
+) -> ParseResult<TransactionRecord> {
+    let mut take = |field: &'static str| lookup(field).ok_or_else(|| ctx.missing(field));
+
+    fn parse<T>(
+        ctx: &FieldContext,
+        field: &'static str,
+        (value, position): (&str, Position),
+    ) -> ParseResult<T>
+    where
+        T: FromStr,
+        FieldError: From<T::Err>,
+    {
+        value
+            .parse::<T>()
+            .map_err(|e| ctx.invalid(field, position, value, e.into()))
+    }
+
+    let tx_id = parse(ctx, "TX_ID", take("TX_ID")?)?;
+    let tx_type: TxType = parse(ctx, "TX_TYPE", take("TX_TYPE")?)?;
+    let from_user_id = parse(ctx, "FROM_USER_ID", take("FROM_USER_ID")?)?;
+    let to_user_id = parse(ctx, "TO_USER_ID", take("TO_USER_ID")?)?;
+
+    // В тексте хранится величина суммы, знак восстанавливается по типу транзакции.
+    // Разбор в i128 отличает отрицательные и слишком большие числа от нечисловых значений
+    let (value, position) = take("AMOUNT")?;
+    let amount: i128 = parse(ctx, "AMOUNT", (value, position))?;
+    let amount = u128::try_from(amount)
+        .map_err(|_| AmountError::NegativeInText)
+        .and_then(|magnitude| amount_from_magnitude(tx_type, magnitude))
+        .map_err(|reason| ctx.invalid_amount(position, value, reason))?;
+
+    let timestamp = parse(ctx, "TIMESTAMP", take("TIMESTAMP")?)?;
+    let status: Status = parse(ctx, "STATUS", take("STATUS")?)?;
+    let description = take("DESCRIPTION")?.0.to_string();
+
+    Ok(TransactionRecord {
+        tx_id,
+        tx_type,
+        from_user_id,
+        to_user_id,
+        amount,
+        timestamp,
+        status,
+        description,
+    })
+}
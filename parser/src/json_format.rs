@@ -0,0 +1,299 @@
+use crate::builder::check_amount_sign;
+use crate::error::{ParseError, ParseResult, Position};
+use crate::{Format, RecordParser, RecordReader, RecordWriter, TransactionRecord};
+use serde_json::value::RawValue;
+use std::io::{BufWriter, Read, Result, Write};
+use std::ops::Range;
+
+/// Коллекция банковских записей, полученная из JSON-файла.
+///
+/// Файл содержит массив объектов, ключи которых совпадают с именами полей спецификации
+/// (`TX_ID`, `TX_TYPE`, ...). Сумма хранится со знаком, как в BIN-формате.
+#[derive(Debug, PartialEq)]
+pub struct YPBankJsonRecords {
+    /// Вектор записей транзакций, извлечённых из JSON.
+    pub records: Vec<TransactionRecord>,
+}
+
+impl YPBankJsonRecords {
+    pub fn new(records: Vec<TransactionRecord>) -> Self {
+        YPBankJsonRecords { records }
+    }
+}
+
+impl RecordParser for YPBankJsonRecords {
+    fn from_read<R: Read>(r: &mut R) -> ParseResult<Self>
+    where
+        Self: Sized,
+    {
+        let records = YPBankJsonReader::new(r).collect::<ParseResult<Vec<_>>>()?;
+        Ok(YPBankJsonRecords { records })
+    }
+
+    fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<()> {
+        let mut json_writer = YPBankJsonWriter::new(writer);
+        for record in &self.records {
+            json_writer.write_record(record)?;
+        }
+        json_writer.finish()
+    }
+}
+
+/// Читатель JSON-массива записей.
+///
+/// Границы элементов массива известны только после разбора всего документа,
+/// поэтому при первом чтении источник загружается в память целиком. Элементы массива
+/// преобразуются в записи по одному: ошибка в одном элементе не мешает чтению следующих.
+pub struct YPBankJsonReader<R: Read> {
+    source: Option<R>,
+    text: String,
+    // Границы ещё не прочитанных элементов массива в text
+    elements: std::vec::IntoIter<Range<usize>>,
+    // Смещение в text и номер строки, до которых подсчитаны переводы строк
+    scanned: (usize, u64),
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    // Исходный текст последней отклонённой записи
+    rejected: Vec<u8>,
+}
+
+impl<R: Read> YPBankJsonReader<R> {
+    pub fn new(r: R) -> Self {
+        YPBankJsonReader {
+            source: Some(r),
+            text: String::new(),
+            elements: Vec::new().into_iter(),
+            scanned: (0, 1),
+            record: 0,
+            rejected: Vec::new(),
+        }
+    }
+
+    fn load(&mut self, mut source: R) -> ParseResult<()> {
+        source.read_to_string(&mut self.text)?;
+        let elements: Vec<&RawValue> =
+            serde_json::from_str(&self.text).map_err(|e| ParseError::Syntax {
+                format: Format::Json,
+                position: Position::Line(e.line() as u64),
+                message: e.to_string(),
+            })?;
+
+        let base = self.text.as_ptr() as usize;
+        self.elements = elements
+            .iter()
+            .map(|element| {
+                let start = element.get().as_ptr() as usize - base;
+                start..start + element.get().len()
+            })
+            .collect::<Vec<_>>()
+            .into_iter();
+        Ok(())
+    }
+
+    // Номер строки, с которой начинается элемент; элементы идут по возрастанию смещений
+    fn line_at(&mut self, offset: usize) -> u64 {
+        let (from, line) = self.scanned;
+        let line = line + self.text[from..offset].matches('\n').count() as u64;
+        self.scanned = (offset, line);
+        line
+    }
+}
+
+impl<R: Read> RecordReader for YPBankJsonReader<R> {
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>> {
+        if let Some(source) = self.source.take() {
+            self.load(source)?;
+        }
+
+        let Some(range) = self.elements.next() else {
+            return Ok(None);
+        };
+        self.record += 1;
+        let line = self.line_at(range.start);
+
+        let result = json_record(Format::Json, self.record, line, &self.text[range.clone()]);
+        if result.is_err() {
+            self.rejected = self.text[range].as_bytes().to_vec();
+        }
+        result.map(Some)
+    }
+
+    fn take_rejected(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rejected)
+    }
+}
+
+/// Преобразует JSON-объект, начинающийся на строке `line`, в запись
+pub(crate) fn json_record(
+    format: Format,
+    record: usize,
+    line: u64,
+    text: &str,
+) -> ParseResult<TransactionRecord> {
+    let tx: TransactionRecord =
+        serde_json::from_str(text).map_err(|e| ParseError::InvalidRecord {
+            format,
+            record,
+            position: Position::Line(line + e.line().saturating_sub(1) as u64),
+            message: e.to_string(),
+        })?;
+
+    check_amount_sign(tx.tx_type, tx.amount).map_err(|reason| ParseError::InvalidAmount {
+        format,
+        record,
+        position: Position::Line(line),
+        value: tx.amount.to_string(),
+        reason,
+    })?;
+    Ok(tx)
+}
+
+/// Потоковый писатель JSON-массива записей.
+///
+/// Каждая запись записывается отдельной строкой массива; закрывающая скобка
+/// записывается при вызове [`RecordWriter::finish`](crate::RecordWriter::finish).
+pub struct YPBankJsonWriter<W: Write> {
+    writer: BufWriter<W>,
+    // Количество уже записанных записей
+    written: usize,
+}
+
+impl<W: Write> YPBankJsonWriter<W> {
+    pub fn new(w: W) -> Self {
+        YPBankJsonWriter {
+            writer: BufWriter::new(w),
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for YPBankJsonWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> Result<()> {
+        let separator = if self.written == 0 { "[\n  " } else { ",\n  " };
+        self.writer.write_all(separator.as_bytes())?;
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let end = if self.written == 0 { "[]\n" } else { "\n]\n" };
+        self.writer.write_all(end.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AmountError, Status, TxType};
+    use std::io::Cursor;
+
+    fn sample_record(tx_id: u64) -> TransactionRecord {
+        TransactionRecord {
+            tx_type: TxType::WITHDRAWAL,
+            status: Status::PENDING,
+            tx_id,
+            from_user_id: 42,
+            to_user_id: 0,
+            amount: -500,
+            timestamp: 1633036860000,
+            description: "Record \"quoted\"".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_read_write_json_records() {
+        let mut records = YPBankJsonRecords::new(vec![sample_record(1), sample_record(2)]);
+
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.starts_with("[\n  {\"TX_ID\":1,\"TX_TYPE\":\"WITHDRAWAL\""));
+
+        let read = YPBankJsonRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(records, read);
+    }
+
+    #[test]
+    fn test_empty_array() {
+        let mut buffer = Vec::new();
+        YPBankJsonRecords::new(Vec::new())
+            .write_to(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, b"[]\n");
+
+        let read = YPBankJsonRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+        assert!(read.records.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_element_reported_with_line() {
+        let data = r#"[
+  {"TX_ID":1,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":1,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""},
+  {"TX_ID":2,"TX_TYPE":"DEPOSITX","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":1,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""},
+  {"TX_ID":3,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":-1,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+]"#;
+        let (records, issues) = crate::parse_lenient(Format::Json, Cursor::new(data)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(issues.len(), 2);
+        assert!(matches!(
+            issues[0].error,
+            ParseError::InvalidRecord {
+                record: 2,
+                position: Position::Line(3),
+                ..
+            }
+        ));
+        assert!(issues[0].raw.starts_with(b"{\"TX_ID\":2,"));
+        assert!(matches!(
+            issues[1].error,
+            ParseError::InvalidAmount {
+                record: 3,
+                position: Position::Line(4),
+                reason: AmountError::NegativeDeposit,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_amount_sign_checked() {
+        let data = r#"
+{"TX_ID":1,"TX_TYPE":"WITHDRAWAL","FROM_USER_ID":1,"TO_USER_ID":0,"AMOUNT":5,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+{"TX_ID":2,"TX_TYPE":"TRANSFER","FROM_USER_ID":1,"TO_USER_ID":2,"AMOUNT":-5,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+{"TX_ID":3,"TX_TYPE":"WITHDRAWAL","FROM_USER_ID":1,"TO_USER_ID":0,"AMOUNT":-5,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+"#;
+        let (records, issues) = crate::parse_lenient(Format::Ndjson, Cursor::new(data)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].amount(), -5);
+        let reasons: Vec<AmountError> = issues
+            .iter()
+            .map(|issue| match issue.error {
+                ParseError::InvalidAmount { reason, .. } => reason,
+                ref other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                AmountError::PositiveWithdrawal,
+                AmountError::NegativeTransfer
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let err =
+            YPBankJsonRecords::from_read(&mut Cursor::new("[\n{\"TX_ID\": 1,\n")).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::Syntax {
+                format: Format::Json,
+                position: Position::Line(3),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{Status, TransactionRecord, TxType};
+use std::collections::BTreeMap;
+
+/// Движение средств по счёту пользователя в результате одной транзакции
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementEntry {
+    pub tx_id: u64,
+    pub tx_type: TxType,
+    pub timestamp: u64,
+    /// Изменение баланса: положительное для зачисления, отрицательное для списания
+    pub change: i128,
+    /// Баланс после применения транзакции
+    pub balance: i128,
+}
+
+/// Транзакция, после которой баланс пользователя стал отрицательным
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overdraft {
+    pub user_id: u64,
+    pub tx_id: u64,
+    pub timestamp: u64,
+    /// Баланс после применения транзакции
+    pub balance: i128,
+}
+
+/// Балансы пользователей, полученные воспроизведением успешных транзакций.
+///
+/// Учитываются только транзакции со статусом SUCCESS, в порядке `TIMESTAMP`
+/// (при равных отметках времени — в порядке `TX_ID`). DEPOSIT зачисляет сумму на `to_user_id`,
+/// WITHDRAWAL списывает с `from_user_id`, TRANSFER переводит с `from_user_id` на `to_user_id`.
+/// Направление движения задаётся типом транзакции, поэтому используется модуль суммы.
+/// Счёт `0` обозначает внешнюю сторону и в балансах не учитывается.
+///
+/// Списание, уводящее баланс в минус, всё равно применяется, а сама транзакция
+/// попадает в список [`Ledger::overdrafts`].
+#[derive(Debug, Default)]
+pub struct Ledger {
+    balances: BTreeMap<u64, i128>,
+    statements: BTreeMap<u64, Vec<StatementEntry>>,
+    overdrafts: Vec<Overdraft>,
+}
+
+impl Ledger {
+    /// Воспроизводит транзакции и возвращает итоговое состояние счетов
+    pub fn replay<'a>(records: impl IntoIterator<Item = &'a TransactionRecord>) -> Self {
+        let mut successful: Vec<&TransactionRecord> = records
+            .into_iter()
+            .filter(|tx| tx.status == Status::SUCCESS)
+            .collect();
+        successful.sort_by_key(|tx| (tx.timestamp, tx.tx_id));
+
+        let mut ledger = Ledger::default();
+        for tx in successful {
+            ledger.apply(tx);
+        }
+        ledger
+    }
+
+    fn apply(&mut self, tx: &TransactionRecord) {
+        let amount = tx.amount_abs() as i128;
+        match tx.tx_type {
+            TxType::DEPOSIT => self.post(tx.to_user_id, tx, amount),
+            TxType::WITHDRAWAL => self.post(tx.from_user_id, tx, -amount),
+            TxType::TRANSFER => {
+                self.post(tx.from_user_id, tx, -amount);
+                self.post(tx.to_user_id, tx, amount);
+            }
+        }
+    }
+
+    fn post(&mut self, user_id: u64, tx: &TransactionRecord, change: i128) {
+        if user_id == 0 {
+            return;
+        }
+
+        let balance = self.balances.entry(user_id).or_default();
+        *balance += change;
+        let balance = *balance;
+
+        self.statements
+            .entry(user_id)
+            .or_default()
+            .push(StatementEntry {
+                tx_id: tx.tx_id,
+                tx_type: tx.tx_type,
+                timestamp: tx.timestamp,
+                change,
+                balance,
+            });
+
+        if change < 0 && balance < 0 {
+            self.overdrafts.push(Overdraft {
+                user_id,
+                tx_id: tx.tx_id,
+                timestamp: tx.timestamp,
+                balance,
+            });
+        }
+    }
+
+    /// Итоговый баланс пользователя; `0`, если по его счёту не было движений
+    pub fn balance(&self, user_id: u64) -> i128 {
+        self.balances.get(&user_id).copied().unwrap_or_default()
+    }
+
+    /// Итоговые балансы всех пользователей в порядке возрастания `user_id`
+    pub fn balances(&self) -> &BTreeMap<u64, i128> {
+        &self.balances
+    }
+
+    /// Выписка по счёту пользователя в порядке применения транзакций
+    pub fn statement(&self, user_id: u64) -> &[StatementEntry] {
+        self.statements
+            .get(&user_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Списания, после которых баланс пользователя стал отрицательным, в порядке применения
+    pub fn overdrafts(&self) -> &[Overdraft] {
+        &self.overdrafts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(
+        tx_id: u64,
+        tx_type: TxType,
+        from_user_id: u64,
+        to_user_id: u64,
+        amount: i64,
+        timestamp: u64,
+    ) -> TransactionRecord {
+        TransactionRecord {
+            tx_id,
+            tx_type,
+            from_user_id,
+            to_user_id,
+            amount,
+            timestamp,
+            status: Status::SUCCESS,
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_replay_in_timestamp_order() {
+        let records = vec![
+            tx(3, TxType::WITHDRAWAL, 2, 0, -30, 300),
+            tx(1, TxType::DEPOSIT, 0, 1, 100, 100),
+            tx(2, TxType::TRANSFER, 1, 2, 40, 200),
+        ];
+        let ledger = Ledger::replay(&records);
+
+        assert_eq!(ledger.balance(1), 60);
+        assert_eq!(ledger.balance(2), 10);
+        assert_eq!(ledger.balance(0), 0);
+        assert_eq!(
+            ledger.balances().iter().collect::<Vec<_>>(),
+            vec![(&1, &60), (&2, &10)]
+        );
+        assert!(ledger.overdrafts().is_empty());
+
+        let ids: Vec<(u64, i128, i128)> = ledger
+            .statement(2)
+            .iter()
+            .map(|e| (e.tx_id, e.change, e.balance))
+            .collect();
+        assert_eq!(ids, vec![(2, 40, 40), (3, -30, 10)]);
+    }
+
+    #[test]
+    fn test_only_successful_transactions_applied() {
+        let mut failed = tx(2, TxType::DEPOSIT, 0, 1, 500, 200);
+        failed.status = Status::FAILURE;
+        let mut pending = tx(3, TxType::WITHDRAWAL, 1, 0, 50, 300);
+        pending.status = Status::PENDING;
+        let records = vec![tx(1, TxType::DEPOSIT, 0, 1, 100, 100), failed, pending];
+
+        let ledger = Ledger::replay(&records);
+        assert_eq!(ledger.balance(1), 100);
+        assert_eq!(ledger.statement(1).len(), 1);
+    }
+
+    #[test]
+    fn test_overdraft_detected() {
+        let records = vec![
+            tx(1, TxType::DEPOSIT, 0, 1, 100, 100),
+            tx(2, TxType::TRANSFER, 1, 2, 150, 200),
+            tx(3, TxType::DEPOSIT, 0, 1, 100, 300),
+        ];
+        let ledger = Ledger::replay(&records);
+
+        assert_eq!(ledger.balance(1), 50);
+        assert_eq!(
+            ledger.overdrafts(),
+            &[Overdraft {
+                user_id: 1,
+                tx_id: 2,
+                timestamp: 200,
+                balance: -50,
+            }]
+        );
+    }
+}
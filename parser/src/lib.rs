@@ -1,38 +1,88 @@
 mod bin_format;
+mod bin_index;
+mod bin_view;
+mod builder;
 mod csv_format;
+mod detect;
 mod error;
+mod fields;
+mod json_format;
+pub mod ledger;
+mod ndjson_format;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 mod txt_format;
+pub mod validate;
 
 use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use std::io::{Read, Result, Write};
 use strum::EnumString;
 
-use crate::bin_format::BinRecord;
-use crate::error::BinToTransError;
+pub use bin_format::{
+    BinFileHeader, BinVersion, BinWriteOptions, CorruptedRegion, MAX_RECORD_SIZE, YPBankBinReader,
+    YPBankBinRecords, YPBankBinWriter,
+};
+pub use bin_index::{BinFileReader, BinIndex, IndexSource};
+#[cfg(feature = "mmap")]
+pub use bin_view::MmapBinFile;
+pub use bin_view::{BinRecordView, BinSliceReader};
+pub use builder::TransactionRecordBuilder;
+pub use csv_format::{YPBankCsvReader, YPBankCsvRecords, YPBankCsvWriter};
+pub use detect::detect_format;
+pub use error::{
+    AmountError, DetectFormatError, FieldError, FileHeaderError, IndexError, ParseError,
+    ParseIssue, ParseResult, Position, RecordBuildError,
+};
+pub use json_format::{YPBankJsonReader, YPBankJsonRecords, YPBankJsonWriter};
+pub use ndjson_format::{YPBankNdjsonReader, YPBankNdjsonRecords, YPBankNdjsonWriter};
+pub use txt_format::{
+    TxtFieldOrder, TxtRecordHeader, TxtWriteOptions, YPBankTxtReader, YPBankTxtRecords,
+    YPBankTxtWriter,
+};
 
-pub use bin_format::YPBankBinRecords;
-pub use csv_format::YPBankCsvRecords;
-pub use txt_format::YPBankTxtRecords;
+/// Поддерживаемые форматы файлов с записями транзакций
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum Format {
+    /// Бинарный формат YPBankBin
+    #[display("bin")]
+    Bin,
+    /// CSV-формат YPBank
+    #[display("csv")]
+    Csv,
+    /// Текстовый формат YPBankText
+    #[display("txt")]
+    Txt,
+    /// JSON-массив записей
+    #[display("json")]
+    Json,
+    /// NDJSON: по одному JSON-объекту записи в строке
+    #[display("ndjson")]
+    Ndjson,
+}
 
-#[derive(Debug, Deserialize, EnumString, Display, PartialEq, Serialize)]
+/// Тип транзакции
+#[derive(Debug, Clone, Copy, Deserialize, EnumString, Display, PartialEq, Eq, Hash, Serialize)]
 #[allow(clippy::upper_case_acronyms)]
-enum TxType {
+pub enum TxType {
     DEPOSIT,
     TRANSFER,
     WITHDRAWAL,
 }
 
-#[derive(Debug, Deserialize, EnumString, Display, PartialEq, Serialize)]
+/// Статус транзакции
+#[derive(Debug, Clone, Copy, Deserialize, EnumString, Display, PartialEq, Eq, Hash, Serialize)]
 #[allow(clippy::upper_case_acronyms)]
-enum Status {
+pub enum Status {
     SUCCESS,
     FAILURE,
     PENDING,
 }
 
 /// Структура формата YP Bank для банковских записей о транзакциях
-#[derive(Debug, Deserialize, Display, Serialize)]
+///
+/// Для создания записи используйте [`TransactionRecord::builder`].
+#[derive(Debug, Clone, Deserialize, Display, Serialize)]
 #[display(
     "TransactionRecord {{
         tx_id: {tx_id},
@@ -53,54 +103,80 @@ pub struct TransactionRecord {
     tx_type: TxType,
     from_user_id: u64,
     to_user_id: u64,
-    amount: u64,
+    /// Сумма со знаком в наименьшей денежной единице, см. [`TransactionRecord::amount`]
+    amount: i64,
     timestamp: u64,
     status: Status,
     description: String,
 }
 
-impl TryFrom<BinRecord> for TransactionRecord {
-    type Error = BinToTransError;
-
-    fn try_from(record: BinRecord) -> std::result::Result<Self, Self::Error> {
-        let tx_type = match record.tx_type {
-            0 => TxType::DEPOSIT,
-            1 => TxType::TRANSFER,
-            2 => TxType::WITHDRAWAL,
-            other => return Err(BinToTransError::InvalidTxType(other)),
-        };
-
-        let status = match record.status {
-            0 => Status::SUCCESS,
-            1 => Status::FAILURE,
-            2 => Status::PENDING,
-            other => return Err(BinToTransError::InvalidStatus(other)),
-        };
-
-        if (record.description.len() as u32) != record.desc_len {
-            return Err(BinToTransError::DescriptionLengthMismatch {
-                expected: record.desc_len,
-                actual: record.description.len(),
-            });
-        }
+impl TransactionRecord {
+    /// Возвращает построитель записи с проверкой согласованности полей
+    pub fn builder() -> TransactionRecordBuilder {
+        TransactionRecordBuilder::default()
+    }
+
+    /// Уникальный id совершенной транзакции
+    pub fn tx_id(&self) -> u64 {
+        self.tx_id
+    }
+
+    /// Тип транзакции
+    pub fn tx_type(&self) -> TxType {
+        self.tx_type
+    }
+
+    /// Счёт отправителя (`0` для DEPOSIT)
+    pub fn from_user_id(&self) -> u64 {
+        self.from_user_id
+    }
+
+    /// Счёт получателя (`0` для WITHDRAWAL)
+    pub fn to_user_id(&self) -> u64 {
+        self.to_user_id
+    }
+
+    /// Сумма со знаком в наименьшей денежной единице.
+    ///
+    /// Знак определяется типом транзакции: DEPOSIT и TRANSFER неотрицательны, WITHDRAWAL (списание)
+    /// неположительна. Построитель и все читатели обеспечивают этот знак, поэтому запись,
+    /// прошедшая через любой формат, остаётся равной исходной:
+    /// - BIN и JSON хранят сумму со знаком; знак, не соответствующий типу (отрицательный DEPOSIT
+    ///   или TRANSFER, положительный WITHDRAWAL), — ошибка;
+    /// - CSV и TXT хранят неотрицательную величину ([`TransactionRecord::amount_abs`]); отрицательное
+    ///   значение — ошибка, при чтении сумма WITHDRAWAL становится отрицательной.
+    ///
+    /// Поэтому величина суммы в тексте не может превышать `i64::MAX` (`2^63` для WITHDRAWAL):
+    /// большие значения отклоняются с [`AmountError::OutOfRange`](crate::AmountError::OutOfRange).
+    pub fn amount(&self) -> i64 {
+        self.amount
+    }
+
+    /// Величина суммы без знака, в которой она представлена в CSV и TXT форматах
+    pub fn amount_abs(&self) -> u64 {
+        self.amount.unsigned_abs()
+    }
+
+    /// Время выполнения транзакции в миллисекундах от эпохи Unix
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Статус транзакции
+    pub fn status(&self) -> Status {
+        self.status
+    }
 
-        Ok(TransactionRecord {
-            tx_id: record.tx_id,
-            tx_type,
-            from_user_id: record.from_user_id,
-            to_user_id: record.to_user_id,
-            amount: record.amount,
-            timestamp: record.timestamp,
-            status,
-            description: record.description,
-        })
+    /// Текстовое описание транзакции
+    pub fn description(&self) -> &str {
+        &self.description
     }
 }
 
 /// Трейт чтения и записи из различных форматов
 pub trait RecordParser {
     /// Парсинг данные из любого источника, реализующего трейт Read
-    fn from_read<R: Read>(_r: &mut R) -> Result<Self>
+    fn from_read<R: Read>(_r: &mut R) -> ParseResult<Self>
     where
         Self: Sized,
     {
@@ -113,7 +189,124 @@ pub trait RecordParser {
     }
 }
 
-// Макрос реализации трейта From для взаимной конвертируемости YPBankBinRecords, YPBankCsvRecords, YPBankTxtRecords
+/// Трейт потокового чтения записей.
+///
+/// Записи извлекаются из источника по одной, поэтому объём используемой памяти
+/// не зависит от размера файла. Каждый читатель также является итератором
+/// по `ParseResult<TransactionRecord>`.
+pub trait RecordReader: Iterator<Item = ParseResult<TransactionRecord>> {
+    /// Читает следующую запись. Возвращает `Ok(None)`, когда источник исчерпан.
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>>;
+
+    /// Забирает исходные данные последней записи, разбор которой завершился ошибкой.
+    /// Возвращает пустой вектор, если таких данных нет.
+    fn take_rejected(&mut self) -> Vec<u8>;
+
+    /// Читает следующую запись в нестрогом режиме.
+    ///
+    /// Ошибка разбора записи возвращается как [`ParseIssue`] вместе с исходными данными,
+    /// и чтение можно продолжить. Ошибки ввода-вывода прерывают чтение.
+    fn read_record_lenient(
+        &mut self,
+    ) -> ParseResult<Option<std::result::Result<TransactionRecord, ParseIssue>>> {
+        match self.read_record() {
+            Ok(record) => Ok(record.map(Ok)),
+            Err(ParseError::Io(e)) => Err(ParseError::Io(e)),
+            Err(error) => Ok(Some(Err(ParseIssue {
+                error,
+                raw: self.take_rejected(),
+            }))),
+        }
+    }
+}
+
+// Макрос реализации трейта Iterator для потоковых читателей через RecordReader::read_record
+macro_rules! impl_iterator_for_readers {
+    ( $( $reader:ident ),* $(,)? ) => {
+        $(
+            impl<R: Read> Iterator for $reader<R> {
+                type Item = ParseResult<TransactionRecord>;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    self.read_record().transpose()
+                }
+            }
+        )*
+    };
+}
+
+impl_iterator_for_readers!(
+    YPBankBinReader,
+    YPBankCsvReader,
+    YPBankTxtReader,
+    YPBankJsonReader,
+    YPBankNdjsonReader
+);
+
+/// Создаёт потоковый читатель записей заданного формата поверх любого источника `Read`
+pub fn record_reader<'a, R: Read + 'a>(format: Format, r: R) -> Box<dyn RecordReader + 'a> {
+    match format {
+        Format::Bin => Box::new(YPBankBinReader::new(r)),
+        Format::Csv => Box::new(YPBankCsvReader::new(r)),
+        Format::Txt => Box::new(YPBankTxtReader::new(r)),
+        Format::Json => Box::new(YPBankJsonReader::new(r)),
+        Format::Ndjson => Box::new(YPBankNdjsonReader::new(r)),
+    }
+}
+
+/// Создаёт потоковый читатель для нестрогого режима.
+///
+/// В отличие от [`record_reader`], BIN-читатель после повреждённого заголовка
+/// продолжает чтение со следующего `MAGIC`.
+pub fn lenient_record_reader<'a, R: Read + 'a>(format: Format, r: R) -> Box<dyn RecordReader + 'a> {
+    match format {
+        Format::Bin => Box::new(YPBankBinReader::skipping_invalid(r)),
+        other => record_reader(other, r),
+    }
+}
+
+/// Читает все записи в нестрогом режиме: некорректные записи пропускаются,
+/// а сведения о них возвращаются вместе с прочитанными записями.
+pub fn parse_lenient<R: Read>(
+    format: Format,
+    r: R,
+) -> ParseResult<(Vec<TransactionRecord>, Vec<ParseIssue>)> {
+    let mut reader = lenient_record_reader(format, r);
+    let mut records = Vec::new();
+    let mut issues = Vec::new();
+    while let Some(result) = reader.read_record_lenient()? {
+        match result {
+            Ok(record) => records.push(record),
+            Err(issue) => issues.push(issue),
+        }
+    }
+    Ok((records, issues))
+}
+
+/// Трейт потоковой записи: записи передаются в приёмник по одной, по мере их появления.
+///
+/// Служебные части формата (заголовок CSV, заголовки записей BIN) формируются самим писателем.
+/// По окончании записи необходимо вызвать [`RecordWriter::finish`].
+pub trait RecordWriter {
+    /// Записывает одну транзакцию
+    fn write_record(&mut self, record: &TransactionRecord) -> Result<()>;
+
+    /// Завершает запись и сбрасывает буферы в приёмник
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Создаёт потоковый писатель записей заданного формата поверх любого приёмника `Write`
+pub fn record_writer<'a, W: Write + 'a>(format: Format, w: W) -> Box<dyn RecordWriter + 'a> {
+    match format {
+        Format::Bin => Box::new(YPBankBinWriter::new(w)),
+        Format::Csv => Box::new(YPBankCsvWriter::new(w)),
+        Format::Txt => Box::new(YPBankTxtWriter::new(w)),
+        Format::Json => Box::new(YPBankJsonWriter::new(w)),
+        Format::Ndjson => Box::new(YPBankNdjsonWriter::new(w)),
+    }
+}
+
+// Макрос реализации трейта From для взаимной конвертируемости коллекций записей всех форматов
 macro_rules! impl_from_for_records {
     ( $( ($from:ident, $to:ident) ),* $(,)? ) => {
         $(
@@ -129,8 +322,22 @@ macro_rules! impl_from_for_records {
 impl_from_for_records!(
     (YPBankBinRecords, YPBankCsvRecords),
     (YPBankBinRecords, YPBankTxtRecords),
+    (YPBankBinRecords, YPBankJsonRecords),
+    (YPBankBinRecords, YPBankNdjsonRecords),
     (YPBankCsvRecords, YPBankBinRecords),
     (YPBankCsvRecords, YPBankTxtRecords),
+    (YPBankCsvRecords, YPBankJsonRecords),
+    (YPBankCsvRecords, YPBankNdjsonRecords),
     (YPBankTxtRecords, YPBankBinRecords),
-    (YPBankTxtRecords, YPBankCsvRecords)
+    (YPBankTxtRecords, YPBankCsvRecords),
+    (YPBankTxtRecords, YPBankJsonRecords),
+    (YPBankTxtRecords, YPBankNdjsonRecords),
+    (YPBankJsonRecords, YPBankBinRecords),
+    (YPBankJsonRecords, YPBankCsvRecords),
+    (YPBankJsonRecords, YPBankTxtRecords),
+    (YPBankJsonRecords, YPBankNdjsonRecords),
+    (YPBankNdjsonRecords, YPBankBinRecords),
+    (YPBankNdjsonRecords, YPBankCsvRecords),
+    (YPBankNdjsonRecords, YPBankTxtRecords),
+    (YPBankNdjsonRecords, YPBankJsonRecords)
 );
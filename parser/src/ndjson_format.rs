@@ -0,0 +1,164 @@
+use crate::error::ParseResult;
+use crate::json_format::json_record;
+use crate::{Format, RecordParser, RecordReader, RecordWriter, TransactionRecord};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Result, Write};
+
+/// Коллекция банковских записей, полученная из NDJSON-файла.
+///
+/// Каждая строка файла содержит один JSON-объект записи с ключами из спецификации
+/// (`TX_ID`, `TX_TYPE`, ...). Пустые строки пропускаются.
+#[derive(Debug, PartialEq)]
+pub struct YPBankNdjsonRecords {
+    /// Вектор записей транзакций, извлечённых из NDJSON.
+    pub records: Vec<TransactionRecord>,
+}
+
+impl YPBankNdjsonRecords {
+    pub fn new(records: Vec<TransactionRecord>) -> Self {
+        YPBankNdjsonRecords { records }
+    }
+}
+
+impl RecordParser for YPBankNdjsonRecords {
+    fn from_read<R: Read>(r: &mut R) -> ParseResult<Self>
+    where
+        Self: Sized,
+    {
+        let records = YPBankNdjsonReader::new(r).collect::<ParseResult<Vec<_>>>()?;
+        Ok(YPBankNdjsonRecords { records })
+    }
+
+    fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<()> {
+        let mut ndjson_writer = YPBankNdjsonWriter::new(writer);
+        for record in &self.records {
+            ndjson_writer.write_record(record)?;
+        }
+        ndjson_writer.finish()
+    }
+}
+
+/// Потоковый читатель NDJSON-файла.
+///
+/// Читает записи по одной через [`RecordReader`](crate::RecordReader), не загружая весь файл в память.
+/// Ошибка в одной строке не мешает чтению следующих строк.
+pub struct YPBankNdjsonReader<R: Read> {
+    lines: Lines<BufReader<R>>,
+    // Номер последней прочитанной строки
+    line: u64,
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    // Исходная строка последней отклонённой записи
+    rejected: Vec<u8>,
+}
+
+impl<R: Read> YPBankNdjsonReader<R> {
+    pub fn new(r: R) -> Self {
+        YPBankNdjsonReader {
+            lines: BufReader::new(r).lines(),
+            line: 0,
+            record: 0,
+            rejected: Vec::new(),
+        }
+    }
+}
+
+impl<R: Read> RecordReader for YPBankNdjsonReader<R> {
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>> {
+        for line in self.lines.by_ref() {
+            let line = line?;
+            self.line += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            self.record += 1;
+
+            let result = json_record(Format::Ndjson, self.record, self.line, &line);
+            if result.is_err() {
+                self.rejected = line.into_bytes();
+                self.rejected.push(b'\n');
+            }
+            return result.map(Some);
+        }
+        Ok(None)
+    }
+
+    fn take_rejected(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.rejected)
+    }
+}
+
+/// Потоковый писатель NDJSON-файла: каждая запись сразу записывается отдельной строкой.
+pub struct YPBankNdjsonWriter<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl<W: Write> YPBankNdjsonWriter<W> {
+    pub fn new(w: W) -> Self {
+        YPBankNdjsonWriter {
+            writer: BufWriter::new(w),
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for YPBankNdjsonWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ParseError, Position};
+    use crate::{Status, TxType};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_write_ndjson_records() {
+        let mut records = YPBankNdjsonRecords::new(vec![TransactionRecord {
+            tx_type: TxType::TRANSFER,
+            status: Status::SUCCESS,
+            tx_id: 1000000000000001,
+            from_user_id: 1,
+            to_user_id: 9223372036854775807,
+            amount: 200,
+            timestamp: 1633036920000,
+            description: "Record number 2".to_string(),
+        }]);
+
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer.iter().filter(|b| **b == b'\n').count(), 1);
+
+        let read = YPBankNdjsonRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(records, read);
+    }
+
+    #[test]
+    fn test_reader_continues_after_bad_line() {
+        let data = r#"{"TX_ID":1,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":1,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+
+{"TX_ID":2,
+{"TX_ID":3,"TX_TYPE":"DEPOSIT","FROM_USER_ID":0,"TO_USER_ID":1,"AMOUNT":1,"TIMESTAMP":1,"STATUS":"SUCCESS","DESCRIPTION":""}
+"#;
+        let (records, issues) = crate::parse_lenient(Format::Ndjson, Cursor::new(data)).unwrap();
+        let ids: Vec<u64> = records.iter().map(|r| r.tx_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            issues[0].error,
+            ParseError::InvalidRecord {
+                format: Format::Ndjson,
+                record: 2,
+                position: Position::Line(3),
+                ..
+            }
+        ));
+        assert_eq!(issues[0].raw, b"{\"TX_ID\":2,\n");
+    }
+}
@@ -0,0 +1,419 @@
+//! Параллельный разбор данных, целиком находящихся в памяти.
+//!
+//! Данные делятся на части по границам записей: BIN — по заголовкам `MAGIC`/`RECORD_SIZE`,
+//! CSV — по переводам строк вне кавычек, TXT — по пустым строкам между блоками. Части разбираются
+//! параллельно в пуле потоков rayon, а записи возвращаются в исходном порядке.
+//! Номера записей, строк и смещения в ошибках указываются относительно всех данных,
+//! как при последовательном чтении.
+
+use rayon::prelude::*;
+use std::io::Read;
+use std::ops::Range;
+
+use crate::bin_format::{FILE_HEADER_SIZE, HEADER_SIZE, frame_len};
+use crate::bin_view::{BinSliceReader, check_body};
+use crate::error::{ParseError, ParseResult, Position};
+use crate::{Format, TransactionRecord, YPBankCsvReader, YPBankTxtReader};
+
+// Минимальный размер части данных, при котором параллельный разбор имеет смысл
+const MIN_CHUNK_SIZE: usize = 256 * 1024;
+
+// Часть данных и число строк перед ней
+struct Chunk {
+    range: Range<usize>,
+    lines_before: u64,
+}
+
+// Результат разбора части: записи до первой ошибки и сама ошибка
+type ChunkResult = (Vec<TransactionRecord>, Option<ParseError>);
+
+/// Разбирает данные заданного формата параллельно и возвращает записи в исходном порядке.
+///
+/// Как и последовательное чтение, возвращает первую по порядку ошибку.
+/// JSON и NDJSON разбираются последовательно.
+pub fn parse_parallel(format: Format, data: &[u8]) -> ParseResult<Vec<TransactionRecord>> {
+    match format {
+        Format::Bin => parse_bin(data),
+        Format::Csv => parse_csv(data),
+        Format::Txt => parse_txt(data),
+        Format::Json | Format::Ndjson => crate::record_reader(format, data).collect(),
+    }
+}
+
+// Размер части: несколько частей на поток, чтобы выровнять нагрузку
+fn chunk_size(data: &[u8]) -> usize {
+    (data.len() / (rayon::current_num_threads() * 4)).max(MIN_CHUNK_SIZE)
+}
+
+fn parse_bin(data: &[u8]) -> ParseResult<Vec<TransactionRecord>> {
+    let reader = BinSliceReader::new(data)?;
+    let header = reader.file_header().copied();
+    let start = if header.is_some() {
+        FILE_HEADER_SIZE
+    } else {
+        0
+    };
+    let checksums = header.is_some_and(|h| h.has_record_checksums());
+
+    // Обход заголовков записей; всё, что осталось после последней целой записи (в том числе
+    // повреждённые данные), попадает в последнюю часть и даёт ту же ошибку, что и при чтении подряд
+    let target = chunk_size(data);
+    let mut chunks = Vec::new();
+    let (mut chunk_start, mut pos) = (start, start);
+    while let Some(frame) = data[pos..].first_chunk::<HEADER_SIZE>() {
+        // Ошибку в заголовке записи сообщит читатель последней части
+        let Ok(len) = frame_len(frame, 0, pos as u64, checksums) else {
+            break;
+        };
+        let next = pos + len;
+        if next > data.len() {
+            break;
+        }
+        pos = next;
+        if pos - chunk_start >= target {
+            chunks.push(chunk_start..pos);
+            chunk_start = pos;
+        }
+    }
+    chunks.push(chunk_start..data.len());
+
+    let results: Vec<ChunkResult> = chunks
+        .par_iter()
+        .map(|range| {
+            let reader = BinSliceReader::chunk(&data[range.clone()], header);
+            collect_chunk(reader.map(|view| view.map(TransactionRecord::from)))
+        })
+        .collect();
+
+    let offsets = chunks.iter().map(|range| range.start as u64);
+    let records = merge(results, offsets.map(|offset| (0, offset)))?;
+    if let Some(header) = header {
+        check_body(data, &header, records.len() as u64)?;
+    }
+    Ok(records)
+}
+
+fn parse_csv(data: &[u8]) -> ParseResult<Vec<TransactionRecord>> {
+    // Заголовок передаётся читателю каждой части, чтобы определить порядок колонок
+    let header_end = data
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(data.len(), |p| p + 1);
+
+    // Чётное число кавычек от начала данных означает, что строка не продолжает поле в кавычках
+    let mut quotes = 0usize;
+    let chunks = split_lines(data, header_end, |line| {
+        quotes += line.iter().filter(|&&b| b == b'"').count();
+        quotes.is_multiple_of(2)
+    });
+
+    let header = &data[..header_end];
+    let results: Vec<ChunkResult> = chunks
+        .par_iter()
+        .map(|chunk| {
+            let body = &data[chunk.range.clone()];
+            collect_chunk(YPBankCsvReader::new(header.chain(body)))
+        })
+        .collect();
+
+    // Читатель части нумерует строки с учётом заголовка
+    let shifts = chunks.iter().map(|chunk| (chunk.lines_before - 1, 0));
+    merge(results, shifts)
+}
+
+fn parse_txt(data: &[u8]) -> ParseResult<Vec<TransactionRecord>> {
+    let chunks = split_lines(data, 0, |line| line.trim_ascii().is_empty());
+
+    let results: Vec<ChunkResult> = chunks
+        .par_iter()
+        .map(|chunk| collect_chunk(YPBankTxtReader::new(&data[chunk.range.clone()])))
+        .collect();
+
+    let shifts = chunks.iter().map(|chunk| (chunk.lines_before, 0));
+    merge(results, shifts)
+}
+
+// Делит данные начиная с start на части по строкам. Часть может закончиться после строки,
+// для которой can_split вернул true; can_split вызывается для каждой строки по порядку
+fn split_lines(data: &[u8], start: usize, mut can_split: impl FnMut(&[u8]) -> bool) -> Vec<Chunk> {
+    let target = chunk_size(data);
+    let lines_before_start = data[..start].iter().filter(|&&b| b == b'\n').count() as u64;
+    let mut chunks = Vec::new();
+    let mut chunk = Chunk {
+        range: start..start,
+        lines_before: lines_before_start,
+    };
+    let lines = data[start..].split_inclusive(|&b| b == b'\n');
+    for (lines, line) in (lines_before_start + 1..).zip(lines) {
+        chunk.range.end += line.len();
+        if can_split(line) && chunk.range.len() >= target {
+            let end = chunk.range.end;
+            chunks.push(std::mem::replace(
+                &mut chunk,
+                Chunk {
+                    range: end..end,
+                    lines_before: lines,
+                },
+            ));
+        }
+    }
+    chunks.push(chunk);
+    chunks
+}
+
+fn collect_chunk(reader: impl Iterator<Item = ParseResult<TransactionRecord>>) -> ChunkResult {
+    let mut records = Vec::new();
+    for record in reader {
+        match record {
+            Ok(record) => records.push(record),
+            Err(e) => return (records, Some(e)),
+        }
+    }
+    (records, None)
+}
+
+// Объединяет результаты частей по порядку. Для каждой части задаётся сдвиг номеров строк
+// и смещений; номера записей сдвигаются на число записей в предыдущих частях
+fn merge(
+    results: Vec<ChunkResult>,
+    shifts: impl Iterator<Item = (u64, u64)>,
+) -> ParseResult<Vec<TransactionRecord>> {
+    let mut all = Vec::with_capacity(results.iter().map(|(records, _)| records.len()).sum());
+    for ((records, error), (lines, offset)) in results.into_iter().zip(shifts) {
+        let before = all.len();
+        all.extend(records);
+        if let Some(e) = error {
+            return Err(shift_error(e, before, lines, offset));
+        }
+    }
+    Ok(all)
+}
+
+// Переводит номер записи, строки и смещение в ошибке части в номера относительно всех данных
+fn shift_error(error: ParseError, records: usize, lines: u64, offset: u64) -> ParseError {
+    let position = |position: Position| match position {
+        Position::Line(line) => Position::Line(line + lines),
+        Position::Offset(o) => Position::Offset(o + offset),
+        Position::LineInRecord { start, line } => Position::LineInRecord {
+            start: start + lines,
+            line: line + lines,
+        },
+    };
+
+    match error {
+        ParseError::MissingField {
+            format,
+            record,
+            position: p,
+            field,
+        } => ParseError::MissingField {
+            format,
+            record: record + records,
+            position: position(p),
+            field,
+        },
+        ParseError::InvalidField {
+            format,
+            record,
+            position: p,
+            field,
+            value,
+            reason,
+        } => ParseError::InvalidField {
+            format,
+            record: record + records,
+            position: position(p),
+            field,
+            value,
+            reason,
+        },
+        ParseError::MalformedLine {
+            format,
+            record,
+            position: p,
+            line,
+        } => ParseError::MalformedLine {
+            format,
+            record: record + records,
+            position: position(p),
+            line,
+        },
+        ParseError::DuplicateField {
+            format,
+            record,
+            position: p,
+            field,
+            line,
+        } => ParseError::DuplicateField {
+            format,
+            record: record + records,
+            position: position(p),
+            field,
+            line,
+        },
+        ParseError::UnknownField {
+            format,
+            record,
+            position: p,
+            field,
+            line,
+        } => ParseError::UnknownField {
+            format,
+            record: record + records,
+            position: position(p),
+            field,
+            line,
+        },
+        ParseError::InvalidRecord {
+            format,
+            record,
+            position: p,
+            message,
+        } => ParseError::InvalidRecord {
+            format,
+            record: record + records,
+            position: position(p),
+            message,
+        },
+        ParseError::InvalidMagic {
+            record,
+            offset: o,
+            magic,
+        } => ParseError::InvalidMagic {
+            record: record + records,
+            offset: o + offset,
+            magic,
+        },
+        ParseError::Truncated {
+            record,
+            offset: o,
+            part,
+            expected,
+            actual,
+        } => ParseError::Truncated {
+            record: record + records,
+            offset: o + offset,
+            part,
+            expected,
+            actual,
+        },
+        ParseError::RecordChecksumMismatch {
+            record,
+            offset: o,
+            tx_id,
+            expected,
+            actual,
+        } => ParseError::RecordChecksumMismatch {
+            record: record + records,
+            offset: o + offset,
+            tx_id,
+            expected,
+            actual,
+        },
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BinVersion, BinWriteOptions, RecordParser, Status, TxType, YPBankBinRecords,
+        YPBankCsvRecords, YPBankTxtRecords,
+    };
+
+    fn records(count: u64) -> Vec<TransactionRecord> {
+        (1..=count)
+            .map(|tx_id| TransactionRecord {
+                tx_type: TxType::DEPOSIT,
+                status: Status::SUCCESS,
+                tx_id,
+                from_user_id: 0,
+                to_user_id: tx_id,
+                amount: tx_id as i64,
+                timestamp: 1633096800000,
+                // Переводы строк и кавычки проверяют границы частей CSV
+                description: format!("Record \"{}\"\nline", tx_id),
+            })
+            .collect()
+    }
+
+    // Достаточно записей, чтобы данные разделились на несколько частей
+    const COUNT: u64 = 20_000;
+
+    fn sequential(format: Format, data: &[u8]) -> ParseResult<Vec<TransactionRecord>> {
+        crate::record_reader(format, data).collect()
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let expected = records(COUNT);
+        let mut bin = Vec::new();
+        YPBankBinRecords::new(expected.clone())
+            .write_to_with_options(
+                &mut bin,
+                BinWriteOptions {
+                    version: BinVersion::V2,
+                    created_at: Some(0),
+                    record_checksums: true,
+                },
+            )
+            .unwrap();
+        let mut csv = Vec::new();
+        YPBankCsvRecords::new(expected.clone())
+            .write_to(&mut csv)
+            .unwrap();
+        let mut txt = Vec::new();
+        YPBankTxtRecords::new(expected.clone())
+            .write_to(&mut txt)
+            .unwrap();
+
+        for (format, data) in [(Format::Bin, bin), (Format::Csv, csv), (Format::Txt, txt)] {
+            assert!(data.len() > 2 * MIN_CHUNK_SIZE, "{}", format);
+            assert_eq!(
+                parse_parallel(format, &data).unwrap(),
+                expected,
+                "{}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_error_positions_match_sequential() {
+        let mut csv = Vec::new();
+        YPBankCsvRecords::new(records(COUNT))
+            .write_to(&mut csv)
+            .unwrap();
+        let mut txt = Vec::new();
+        YPBankTxtRecords::new(records(COUNT))
+            .write_to(&mut txt)
+            .unwrap();
+        let mut bin = Vec::new();
+        YPBankBinRecords::new(records(COUNT))
+            .write_to(&mut bin)
+            .unwrap();
+
+        // Порча данных ближе к концу, чтобы ошибка оказалась не в первой части
+        let at = |data: &[u8], needle: &[u8]| {
+            data.windows(needle.len())
+                .rposition(|w| w == needle)
+                .unwrap()
+        };
+        let p = at(&csv, b",DEPOSIT,");
+        csv[p + 1] = b'X';
+        let p = at(&txt, b"TX_TYPE: DEPOSIT");
+        txt[p + 9] = b'X';
+        let p = at(&bin, b"YPBN");
+        bin[p] = b'X';
+
+        for (format, data) in [(Format::Csv, csv), (Format::Txt, txt), (Format::Bin, bin)] {
+            let expected = sequential(format, &data)
+                .map(|_| ())
+                .unwrap_err()
+                .to_string();
+            let actual = parse_parallel(format, &data).unwrap_err().to_string();
+            assert_eq!(actual, expected, "{}", format);
+        }
+    }
+}
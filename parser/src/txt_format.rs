@@ -1,7 +1,8 @@
-use crate::{RecordParser, Status, TransactionRecord, TxType};
+use crate::error::{ParseError, ParseResult, Position};
+use crate::fields::{FIELD_NAMES, FieldContext, record_from_fields};
+use crate::{Format, RecordParser, RecordReader, RecordWriter, TransactionRecord, TxType};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
-use std::str::FromStr;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Result, Write};
 
 /// Коллекция банковских записей, полученная из TXT-файла формата YP Bank.
 ///
@@ -20,129 +21,381 @@ impl YPBankTxtRecords {
 }
 
 impl RecordParser for YPBankTxtRecords {
-    fn from_read<R: Read>(r: &mut R) -> Result<Self>
+    fn from_read<R: Read>(r: &mut R) -> ParseResult<Self>
     where
         Self: Sized,
     {
-        let mut records = Vec::new();
-        let buffer = BufReader::new(r);
-        let mut current_map = HashMap::new();
-        for buff_line in buffer.lines() {
-            let line = trim_line(buff_line)?;
-            if line.starts_with("#") {
-                continue;
-            }
-            if line.is_empty() {
-                if !current_map.is_empty() {
-                    let record = hashmap_to_record(&mut current_map)?;
-                    records.push(record);
-                }
-                current_map.clear();
-            } else {
-                add_line_to_map(line, &mut current_map)?;
-            }
-        }
-        if !current_map.is_empty() {
-            let record = hashmap_to_record(&mut current_map)?;
-            records.push(record);
-            current_map.clear();
-        }
+        let records = YPBankTxtReader::new(r).collect::<ParseResult<Vec<_>>>()?;
 
         Ok(YPBankTxtRecords { records })
     }
 
     fn write_to<W: Write>(&mut self, writer: &mut W) -> Result<()> {
+        let mut txt_writer = YPBankTxtWriter::new(writer);
         for record in &self.records {
-            write_record_to(writer, record)?;
+            txt_writer.write_record(record)?;
+        }
+        txt_writer.finish()
+    }
+}
+
+/// Потоковый читатель TXT-файла формата YP Bank.
+///
+/// Читает записи по одной через [`RecordReader`](crate::RecordReader), не загружая весь файл в память.
+/// Ошибка в одном блоке не мешает чтению следующих блоков.
+///
+/// Каждое поле спецификации должно встречаться в блоке ровно один раз: повторное поле
+/// и ключ, не входящий в спецификацию, считаются ошибкой. Читатель из
+/// [`YPBankTxtReader::with_extensions`] вместо ошибки сохраняет неизвестные ключи,
+/// они доступны через [`YPBankTxtReader::extensions`].
+pub struct YPBankTxtReader<R: Read> {
+    lines: Lines<BufReader<R>>,
+    // Номер последней прочитанной строки
+    line: u64,
+    // Порядковый номер последней прочитанной записи
+    record: usize,
+    // Исходные строки текущего блока
+    block: Vec<u8>,
+    keep_extensions: bool,
+    // Неизвестные ключи последней прочитанной записи
+    extensions: Vec<(String, String)>,
+    // Поля спецификации последней прочитанной записи в порядке их следования
+    order: Vec<&'static str>,
+}
+
+impl<R: Read> YPBankTxtReader<R> {
+    pub fn new(r: R) -> Self {
+        YPBankTxtReader {
+            lines: BufReader::new(r).lines(),
+            line: 0,
+            record: 0,
+            block: Vec::new(),
+            keep_extensions: false,
+            extensions: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+
+    /// Создаёт читатель, сохраняющий ключи, не входящие в спецификацию, как метаданные расширений
+    pub fn with_extensions(r: R) -> Self {
+        YPBankTxtReader {
+            keep_extensions: true,
+            ..YPBankTxtReader::new(r)
+        }
+    }
+
+    /// Неизвестные ключи последней прочитанной записи со значениями, в порядке их следования.
+    /// Всегда пуст, если читатель создан не через [`YPBankTxtReader::with_extensions`].
+    pub fn extensions(&self) -> &[(String, String)] {
+        &self.extensions
+    }
+
+    /// Поля спецификации последней прочитанной записи в порядке их следования в блоке
+    pub fn field_order(&self) -> &[&'static str] {
+        &self.order
+    }
+
+    // Ошибку в строке блока сопровождает пропуск оставшихся строк блока
+    fn reject_line(&mut self, error: ParseError) -> ParseResult<Option<TransactionRecord>> {
+        self.skip_block()?;
+        Err(error)
+    }
+
+    fn next_line(&mut self) -> ParseResult<Option<String>> {
+        match self.lines.next() {
+            Some(line) => {
+                self.line += 1;
+                Ok(Some(line?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn push_block_line(&mut self, line: &str) {
+        self.block.extend_from_slice(line.as_bytes());
+        self.block.push(b'\n');
+    }
+
+    // Пропускает оставшиеся строки текущего блока после ошибки
+    fn skip_block(&mut self) -> ParseResult<()> {
+        while let Some(line) = self.next_line()? {
+            if line.trim().is_empty() {
+                break;
+            }
+            self.push_block_line(&line);
         }
         Ok(())
     }
 }
 
-fn write_record_to<W: Write>(w: &mut W, record: &TransactionRecord) -> Result<()> {
-    let record_number = record
-        .description
-        .split(' ')
-        .next_back()
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(0);
+impl<R: Read> RecordReader for YPBankTxtReader<R> {
+    fn read_record(&mut self) -> ParseResult<Option<TransactionRecord>> {
+        let mut fields: HashMap<&'static str, (String, u64)> = HashMap::new();
+        let mut start_line = None;
+        self.block.clear();
+        self.extensions.clear();
+        self.order.clear();
+
+        while let Some(raw_line) = self.next_line()? {
+            let line = raw_line.trim();
+            if line.is_empty() {
+                if start_line.is_some() {
+                    break;
+                }
+                continue;
+            }
+            if start_line.is_some() {
+                self.push_block_line(&raw_line);
+            }
+            if line.starts_with("#") {
+                continue;
+            }
+            let start = *start_line.get_or_insert_with(|| {
+                self.record += 1;
+                self.line
+            });
+            if start == self.line {
+                self.push_block_line(&raw_line);
+            }
+            let position = Position::LineInRecord {
+                start,
+                line: self.line,
+            };
+
+            let Some((key, value)) = line.split_once(": ") else {
+                return self.reject_line(ParseError::MalformedLine {
+                    format: Format::Txt,
+                    record: self.record,
+                    position,
+                    line: line.to_string(),
+                });
+            };
+            match FIELD_NAMES.iter().find(|name| **name == key) {
+                Some(field) if fields.contains_key(field) => {
+                    return self.reject_line(ParseError::DuplicateField {
+                        format: Format::Txt,
+                        record: self.record,
+                        position,
+                        field,
+                        line: line.to_string(),
+                    });
+                }
+                Some(field) => {
+                    fields.insert(field, (unquote(value), self.line));
+                    self.order.push(field);
+                }
+                None if self.keep_extensions => {
+                    self.extensions.push((key.to_string(), unquote(value)));
+                }
+                None => {
+                    return self.reject_line(ParseError::UnknownField {
+                        format: Format::Txt,
+                        record: self.record,
+                        position,
+                        field: key.to_string(),
+                        line: line.to_string(),
+                    });
+                }
+            }
+        }
 
-    writeln!(w, "# Record {} {}", record_number, record.tx_type)?;
+        let Some(start) = start_line else {
+            return Ok(None);
+        };
+        let ctx = FieldContext {
+            format: Format::Txt,
+            record: self.record,
+            position: Position::LineInRecord { start, line: start },
+        };
+        let result = record_from_fields(&ctx, |field| {
+            fields.get(field).map(|(value, line)| {
+                (
+                    value.as_str(),
+                    Position::LineInRecord { start, line: *line },
+                )
+            })
+        });
+        if result.is_ok() {
+            self.block.clear();
+        }
+        result.map(Some)
+    }
 
-    writeln!(w, "TX_ID: {}", record.tx_id)?;
-    writeln!(w, "TX_TYPE: {}", record.tx_type)?;
-    writeln!(w, "FROM_USER_ID: {}", record.from_user_id)?;
-    writeln!(w, "TO_USER_ID: {}", record.to_user_id)?;
-    writeln!(w, "AMOUNT: {}", record.amount)?;
-    writeln!(w, "TIMESTAMP: {}", record.timestamp)?;
-    writeln!(w, "STATUS: {}", record.status)?;
-    writeln!(w, "DESCRIPTION: \"{}\"", record.description)?;
+    fn take_rejected(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.block)
+    }
+}
+
+/// Вид строки-комментария перед каждой записью TXT-файла
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxtRecordHeader {
+    /// Комментарий в стиле спецификации с порядковым номером записи: `# Record 1 (Deposit)`
+    #[default]
+    Spec,
+    /// Записи выводятся без комментария
+    None,
+}
 
-    writeln!(w)?;
+/// Порядок полей в блоке записи TXT-файла
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TxtFieldOrder {
+    /// Порядок спецификации: `TX_ID`, `TX_TYPE`, ..., `DESCRIPTION`
+    #[default]
+    Canonical,
+    /// Порядок полей исходной записи, переданный в [`YPBankTxtWriter::write_record_in_order`].
+    /// Записи, переданные через [`RecordWriter::write_record`], выводятся в порядке спецификации.
+    Preserve,
+}
 
-    Ok(())
+/// Параметры записи TXT-файла
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxtWriteOptions {
+    pub header: TxtRecordHeader,
+    pub field_order: TxtFieldOrder,
 }
 
-fn add_line_to_map(line: String, map: &mut HashMap<String, String>) -> Result<()> {
-    let (k, v) = match line.split_once(": ") {
-        Some((k, v)) => (k, v),
-        None => {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("Неправильный формат строки: '{}'", line),
-            ));
+/// Потоковый писатель TXT-файла формата YP Bank.
+///
+/// Каждая запись сразу сериализуется в блок `KEY: value`, отделённый пустой строкой.
+/// Записи нумеруются последовательно с 1 в порядке записи, вид комментария и порядок полей
+/// задаются через [`TxtWriteOptions`].
+pub struct YPBankTxtWriter<W: Write> {
+    writer: BufWriter<W>,
+    options: TxtWriteOptions,
+    // Количество уже записанных записей
+    written: usize,
+}
+
+impl<W: Write> YPBankTxtWriter<W> {
+    pub fn new(w: W) -> Self {
+        YPBankTxtWriter::with_options(w, TxtWriteOptions::default())
+    }
+
+    pub fn with_options(w: W, options: TxtWriteOptions) -> Self {
+        YPBankTxtWriter {
+            writer: BufWriter::new(w),
+            options,
+            written: 0,
         }
-    };
+    }
 
-    let v_clean = v.replace('"', "");
+    /// Записывает запись с полями в заданном порядке, если выбран [`TxtFieldOrder::Preserve`].
+    ///
+    /// Порядок обычно берётся из [`YPBankTxtReader::field_order`]; поля, которых в нём нет,
+    /// выводятся следом в порядке спецификации.
+    pub fn write_record_in_order(
+        &mut self,
+        record: &TransactionRecord,
+        order: &[&'static str],
+    ) -> Result<()> {
+        self.written += 1;
+        if self.options.header == TxtRecordHeader::Spec {
+            writeln!(
+                self.writer,
+                "# Record {} ({})",
+                self.written,
+                spec_type_name(record.tx_type)
+            )?;
+        }
+
+        let order = match self.options.field_order {
+            TxtFieldOrder::Canonical => &[],
+            TxtFieldOrder::Preserve => order,
+        };
+        let remaining = FIELD_NAMES.iter().filter(|field| !order.contains(field));
+        for field in order.iter().chain(remaining) {
+            write_field(&mut self.writer, record, field)?;
+        }
 
-    map.insert(k.to_string(), v_clean.to_string());
-    Ok(())
+        writeln!(self.writer)
+    }
 }
 
-fn trim_line(line: Result<String>) -> Result<String> {
-    let line = line?;
-    Ok(line.trim().to_string())
+impl<W: Write> RecordWriter for YPBankTxtWriter<W> {
+    fn write_record(&mut self, record: &TransactionRecord) -> Result<()> {
+        self.write_record_in_order(record, &[])
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
 }
 
-/// Преобразует накопленный HashMap в структуру TransactionRecord.
-/// После успешного преобразования все использованные ключи удаляются из карты.
-fn hashmap_to_record(map: &mut HashMap<String, String>) -> Result<TransactionRecord> {
-    // Функция-помощник для извлечения и парсинга строки
-    fn take_string(map: &mut HashMap<String, String>, key: &str) -> Result<String> {
-        map.remove(key)
-            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("Отсутствует ключ: {}", key)))
+// Название типа транзакции в комментарии записи, как в примерах спецификации: `Deposit`
+fn spec_type_name(tx_type: TxType) -> &'static str {
+    match tx_type {
+        TxType::DEPOSIT => "Deposit",
+        TxType::TRANSFER => "Transfer",
+        TxType::WITHDRAWAL => "Withdrawal",
     }
+}
 
-    fn take_parse<T>(map: &mut HashMap<String, String>, key: &str) -> Result<T>
-    where
-        T: FromStr,
-        T::Err: std::fmt::Display,
-    {
-        let s = take_string(map, key)?;
-        s.parse::<T>().map_err(|e| {
-            Error::new(
-                ErrorKind::InvalidData,
-                format!("Ошибка парсинга '{}': {}", key, e),
-            )
-        })
-    }
-
-    Ok(TransactionRecord {
-        tx_id: take_parse(map, "TX_ID")?,
-        tx_type: take_parse::<TxType>(map, "TX_TYPE")?,
-        from_user_id: take_parse(map, "FROM_USER_ID")?,
-        to_user_id: take_parse(map, "TO_USER_ID")?,
-        amount: take_parse(map, "AMOUNT")?,
-        timestamp: take_parse(map, "TIMESTAMP")?,
-        status: take_parse::<Status>(map, "STATUS")?,
-        description: take_string(map, "DESCRIPTION")?,
-    })
+fn write_field<W: Write>(w: &mut W, record: &TransactionRecord, field: &str) -> Result<()> {
+    match field {
+        "TX_ID" => writeln!(w, "TX_ID: {}", record.tx_id),
+        "TX_TYPE" => writeln!(w, "TX_TYPE: {}", record.tx_type),
+        "FROM_USER_ID" => writeln!(w, "FROM_USER_ID: {}", record.from_user_id),
+        "TO_USER_ID" => writeln!(w, "TO_USER_ID: {}", record.to_user_id),
+        "AMOUNT" => writeln!(w, "AMOUNT: {}", record.amount_abs()),
+        "TIMESTAMP" => writeln!(w, "TIMESTAMP: {}", record.timestamp),
+        "STATUS" => writeln!(w, "STATUS: {}", record.status),
+        "DESCRIPTION" => writeln!(w, "DESCRIPTION: \"{}\"", escape(&record.description)),
+        _ => Ok(()),
+    }
+}
+
+/// Экранирует значение для записи в кавычках: `\` → `\\`, `"` → `\"`,
+/// перевод строки → `\n`, возврат каретки → `\r`
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Возвращает значение поля: значение в кавычках освобождается от них и разэкранируется,
+/// значение без кавычек возвращается как есть.
+///
+/// Неизвестные последовательности `\x` и одиночные кавычки внутри значения сохраняются без изменений,
+/// поэтому файлы, записанные без экранирования, читаются так же, как раньше.
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('"') => unescaped.push('"'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => {
+                unescaped.push('\\');
+                unescaped.push(other);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{AmountError, Status, TxType};
     use std::io::Cursor;
 
     #[test]
@@ -186,8 +439,15 @@ DESCRIPTION: \"test\"
         let result = YPBankTxtRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        assert!(err.to_string().contains("Неправильный формат строки"));
+        assert!(matches!(
+            err,
+            ParseError::MalformedLine {
+                format: Format::Txt,
+                record: 1,
+                position: Position::LineInRecord { start: 2, line: 3 },
+                ref line,
+            } if line == "TX_TYPE DEPOSIT"
+        ));
     }
 
     #[test]
@@ -208,8 +468,15 @@ DESCRIPTION: \"test\"
         let result = YPBankTxtRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        assert!(err.to_string().contains("Отсутствует ключ: TX_TYPE"));
+        assert!(matches!(
+            err,
+            ParseError::MissingField {
+                record: 1,
+                position: Position::LineInRecord { start: 2, line: 2 },
+                field: "TX_TYPE",
+                ..
+            }
+        ));
     }
 
     #[test]
@@ -230,8 +497,60 @@ DESCRIPTION: \"test\"
         let result = YPBankTxtRecords::from_read(&mut cursor);
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert_eq!(err.kind(), ErrorKind::InvalidData);
-        assert!(err.to_string().contains("Ошибка парсинга 'TX_TYPE'"));
+        assert!(matches!(
+            err,
+            ParseError::InvalidField {
+                position: Position::LineInRecord { start: 2, line: 3 },
+                field: "TX_TYPE",
+                ref value,
+                ..
+            } if value == "5"
+        ));
+    }
+
+    #[test]
+    fn test_reader_continues_after_bad_block() {
+        let data = "\
+TX_ID: 1
+TX_TYPE DEPOSIT
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 456
+AMOUNT: 1000
+TIMESTAMP: 1633036860
+STATUS: SUCCESS
+DESCRIPTION: \"test\"
+";
+        let mut reader = YPBankTxtReader::new(Cursor::new(data));
+        assert!(reader.read_record().is_err());
+        let record = reader.read_record().unwrap().unwrap();
+        assert_eq!(record.tx_id, 2);
+        assert!(reader.read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_negative_amount_rejected() {
+        let data = "\
+TX_ID: 123
+TX_TYPE: WITHDRAWAL
+FROM_USER_ID: 456
+TO_USER_ID: 0
+AMOUNT: -1000
+TIMESTAMP: 1633036860
+STATUS: SUCCESS
+DESCRIPTION: \"test\"
+";
+        let err = YPBankTxtRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::InvalidAmount {
+                position: Position::LineInRecord { start: 1, line: 5 },
+                reason: AmountError::NegativeInText,
+                ..
+            }
+        ));
     }
 
     #[test]
@@ -242,4 +561,179 @@ DESCRIPTION: \"test\"
         assert!(result.is_ok());
         assert_eq!(result.unwrap().records.len(), 0);
     }
+
+    #[test]
+    fn test_lenient_keeps_raw_block() {
+        let data = "\
+# Record 1 DEPOSIT
+TX_ID: 1
+TX_TYPE DEPOSIT
+
+TX_ID: 2
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 456
+AMOUNT: 1000
+TIMESTAMP: 1633036860
+STATUS: SUCCESS
+DESCRIPTION: \"test\"
+";
+        let (records, issues) = crate::parse_lenient(Format::Txt, Cursor::new(data)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].tx_id, 2);
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(issues[0].error, ParseError::MalformedLine { .. }));
+        assert_eq!(issues[0].raw, b"TX_ID: 1\nTX_TYPE DEPOSIT\n");
+    }
+
+    const VALID_BLOCK: &str = "\
+TX_ID: 123
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 456
+AMOUNT: 1000
+TIMESTAMP: 1633036860
+STATUS: SUCCESS
+DESCRIPTION: \"test\"
+";
+
+    #[test]
+    fn test_duplicate_field_rejected() {
+        let data = format!("# Record 1\n{}AMOUNT: 2000\n", VALID_BLOCK);
+        let err = YPBankTxtRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert!(matches!(
+            err,
+            ParseError::DuplicateField {
+                record: 1,
+                position: Position::LineInRecord { start: 2, line: 10 },
+                field: "AMOUNT",
+                ref line,
+                ..
+            } if line == "AMOUNT: 2000"
+        ));
+    }
+
+    #[test]
+    fn test_unknown_field_rejected() {
+        let data = format!("{}CURRENCY: EUR\n", VALID_BLOCK);
+        let err = YPBankTxtRecords::from_read(&mut Cursor::new(data)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "txt record 1 at line 9 (record starts at line 1): unknown field \"CURRENCY\" in line \"CURRENCY: EUR\""
+        );
+    }
+
+    #[test]
+    fn test_extensions_kept() {
+        let data = format!("CURRENCY: \"EUR\"\n{}\n{}", VALID_BLOCK, VALID_BLOCK);
+        let mut reader = YPBankTxtReader::with_extensions(Cursor::new(data));
+
+        assert_eq!(reader.read_record().unwrap().unwrap().tx_id, 123);
+        assert_eq!(
+            reader.extensions(),
+            &[("CURRENCY".to_string(), "EUR".to_string())]
+        );
+        assert!(reader.read_record().unwrap().is_some());
+        assert!(reader.extensions().is_empty());
+    }
+
+    fn record_with_description(description: &str) -> TransactionRecord {
+        TransactionRecord {
+            tx_type: TxType::DEPOSIT,
+            status: Status::SUCCESS,
+            tx_id: 1,
+            from_user_id: 0,
+            to_user_id: 10,
+            amount: 100,
+            timestamp: 1633036860000,
+            description: description.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_description_escaping() {
+        let mut records =
+            YPBankTxtRecords::new(vec![record_with_description("Say \"hi\"\nC:\\temp\\")]);
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert!(text.contains("DESCRIPTION: \"Say \\\"hi\\\"\\nC:\\\\temp\\\\\"\n"));
+
+        let read = YPBankTxtRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(records, read);
+    }
+
+    #[test]
+    fn test_headers_numbered_sequentially() {
+        let mut records = YPBankTxtRecords::new(vec![
+            record_with_description("Record number 7"),
+            record_with_description(""),
+        ]);
+        let mut buffer = Vec::new();
+        records.write_to(&mut buffer).unwrap();
+
+        let text = String::from_utf8(buffer).unwrap();
+        let headers: Vec<&str> = text.lines().filter(|l| l.starts_with('#')).collect();
+        assert_eq!(
+            headers,
+            vec!["# Record 1 (Deposit)", "# Record 2 (Deposit)"]
+        );
+    }
+
+    #[test]
+    fn test_write_options() {
+        let options = TxtWriteOptions {
+            header: TxtRecordHeader::None,
+            field_order: TxtFieldOrder::Preserve,
+        };
+        let data = "\
+STATUS: SUCCESS
+TX_ID: 1
+TX_TYPE: DEPOSIT
+FROM_USER_ID: 0
+TO_USER_ID: 10
+AMOUNT: 100
+TIMESTAMP: 1633036860000
+DESCRIPTION: \"x\"
+";
+        let mut reader = YPBankTxtReader::new(Cursor::new(data));
+        let record = reader.read_record().unwrap().unwrap();
+        assert_eq!(reader.field_order()[0], "STATUS");
+
+        let mut buffer = Vec::new();
+        let mut writer = YPBankTxtWriter::with_options(&mut buffer, options);
+        writer
+            .write_record_in_order(&record, reader.field_order())
+            .unwrap();
+        writer.write_record(&record).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        let text = String::from_utf8(buffer).unwrap();
+        let (first, second) = text.split_once("\n\n").unwrap();
+        assert_eq!(format!("{}\n", first), data);
+        assert!(second.starts_with("TX_ID: 1\n"));
+        assert!(!text.contains('#'));
+    }
+
+    #[test]
+    fn test_unescaped_legacy_description() {
+        assert_eq!(unquote("\"Record number 1\""), "Record number 1");
+        assert_eq!(unquote("\"Say \"hi\"\""), "Say \"hi\"");
+        assert_eq!(unquote("\"C:\\path\\\""), "C:\\path\\");
+        assert_eq!(unquote("100"), "100");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_description_round_trip(description in "\\PC*|[\"\\\\\r\n nr]{0,8}") {
+            let mut records = YPBankTxtRecords::new(vec![record_with_description(&description)]);
+            let mut buffer = Vec::new();
+            records.write_to(&mut buffer).unwrap();
+
+            let read = YPBankTxtRecords::from_read(&mut Cursor::new(buffer)).unwrap();
+            proptest::prop_assert_eq!(records, read);
+        }
+    }
 }
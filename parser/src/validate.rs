@@ -0,0 +1,252 @@
+use crate::TransactionRecord;
+use crate::builder::check_user_ids;
+use derive_more::Display;
+use std::collections::HashMap;
+
+/// Правило семантической проверки записей.
+///
+/// Записи передаются правилу по очереди, в порядке их следования в источнике,
+/// поэтому правило может хранить состояние между вызовами (например, уже встреченные `TX_ID`).
+pub trait ValidationRule {
+    /// Короткое имя правила, под которым нарушения попадают в отчёт
+    fn name(&self) -> &'static str;
+
+    /// Проверяет запись с порядковым номером `record` (с 1). Возвращает описание нарушения
+    fn check(&mut self, record: usize, tx: &TransactionRecord) -> Result<(), String>;
+}
+
+/// Счета отправителя и получателя соответствуют типу транзакции:
+/// у DEPOSIT `from_user_id == 0`, у WITHDRAWAL `to_user_id == 0`,
+/// у TRANSFER оба счёта ненулевые и различны.
+#[derive(Debug, Default)]
+pub struct UserAccountsRule;
+
+impl ValidationRule for UserAccountsRule {
+    fn name(&self) -> &'static str {
+        "user-accounts"
+    }
+
+    fn check(&mut self, _record: usize, tx: &TransactionRecord) -> Result<(), String> {
+        check_user_ids(tx.tx_type, tx.from_user_id, tx.to_user_id).map_err(|e| e.to_string())
+    }
+}
+
+/// `TX_ID` не повторяется в пределах источника
+#[derive(Debug, Default)]
+pub struct UniqueTxIdRule {
+    // TX_ID и номер записи, в которой он встретился впервые
+    seen: HashMap<u64, usize>,
+}
+
+impl ValidationRule for UniqueTxIdRule {
+    fn name(&self) -> &'static str {
+        "unique-tx-id"
+    }
+
+    fn check(&mut self, record: usize, tx: &TransactionRecord) -> Result<(), String> {
+        match self.seen.get(&tx.tx_id) {
+            Some(first) => Err(format!("duplicate TX_ID, first seen in record {}", first)),
+            None => {
+                self.seen.insert(tx.tx_id, record);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `TIMESTAMP` (миллисекунды Unix-времени) лежит в допустимом диапазоне `[min, max]`
+#[derive(Debug, Clone, Copy)]
+pub struct TimestampRangeRule {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl TimestampRangeRule {
+    /// 2000-01-01T00:00:00Z
+    pub const DEFAULT_MIN: u64 = 946_684_800_000;
+    /// 2100-01-01T00:00:00Z
+    pub const DEFAULT_MAX: u64 = 4_102_444_800_000;
+
+    pub fn new(min: u64, max: u64) -> Self {
+        TimestampRangeRule { min, max }
+    }
+}
+
+impl Default for TimestampRangeRule {
+    fn default() -> Self {
+        TimestampRangeRule::new(Self::DEFAULT_MIN, Self::DEFAULT_MAX)
+    }
+}
+
+impl ValidationRule for TimestampRangeRule {
+    fn name(&self) -> &'static str {
+        "timestamp-range"
+    }
+
+    fn check(&mut self, _record: usize, tx: &TransactionRecord) -> Result<(), String> {
+        if (self.min..=self.max).contains(&tx.timestamp) {
+            Ok(())
+        } else {
+            Err(format!(
+                "TIMESTAMP {} is outside of range [{}, {}]",
+                tx.timestamp, self.min, self.max
+            ))
+        }
+    }
+}
+
+/// Нарушение правила проверки
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[display("record {record} (TX_ID {tx_id}): [{rule}] {message}")]
+pub struct Violation {
+    /// Порядковый номер записи в источнике (с 1)
+    pub record: usize,
+    pub tx_id: u64,
+    /// Имя нарушенного правила
+    pub rule: &'static str,
+    pub message: String,
+}
+
+/// Итог проверки набора записей
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Количество проверенных записей
+    pub records_checked: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    /// `true`, если нарушений не найдено
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Проверяет записи набором правил и собирает нарушения в [`ValidationReport`].
+///
+/// [`Validator::default`] содержит все встроенные правила; [`Validator::new`] создаёт
+/// пустой набор, который дополняется через [`Validator::rule`].
+pub struct Validator {
+    rules: Vec<Box<dyn ValidationRule>>,
+    report: ValidationReport,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator::new()
+            .rule(UserAccountsRule)
+            .rule(UniqueTxIdRule::default())
+            .rule(TimestampRangeRule::default())
+    }
+}
+
+impl Validator {
+    /// Создаёт валидатор без правил
+    pub fn new() -> Self {
+        Validator {
+            rules: Vec::new(),
+            report: ValidationReport::default(),
+        }
+    }
+
+    /// Добавляет правило проверки
+    pub fn rule(mut self, rule: impl ValidationRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Проверяет очередную запись всеми правилами
+    pub fn check(&mut self, tx: &TransactionRecord) {
+        self.report.records_checked += 1;
+        let record = self.report.records_checked;
+        for rule in &mut self.rules {
+            if let Err(message) = rule.check(record, tx) {
+                self.report.violations.push(Violation {
+                    record,
+                    tx_id: tx.tx_id,
+                    rule: rule.name(),
+                    message,
+                });
+            }
+        }
+    }
+
+    /// Завершает проверку и возвращает отчёт
+    pub fn finish(self) -> ValidationReport {
+        self.report
+    }
+
+    /// Проверяет все записи и возвращает отчёт
+    pub fn validate<'a>(
+        mut self,
+        records: impl IntoIterator<Item = &'a TransactionRecord>,
+    ) -> ValidationReport {
+        for tx in records {
+            self.check(tx);
+        }
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Status, TxType};
+
+    fn transfer(tx_id: u64, from_user_id: u64, to_user_id: u64) -> TransactionRecord {
+        TransactionRecord {
+            tx_id,
+            tx_type: TxType::TRANSFER,
+            from_user_id,
+            to_user_id,
+            amount: 100,
+            timestamp: 1633036860000,
+            status: Status::SUCCESS,
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_valid_records() {
+        let records = [transfer(1, 10, 20), transfer(2, 20, 10)];
+        let report = Validator::default().validate(&records);
+        assert_eq!(report.records_checked, 2);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn test_default_rules_report_violations() {
+        let mut late = transfer(3, 10, 20);
+        late.timestamp = TimestampRangeRule::DEFAULT_MAX + 1;
+        let records = [transfer(1, 10, 10), transfer(1, 10, 20), late];
+
+        let report = Validator::default().validate(&records);
+        let found: Vec<(usize, &str)> = report
+            .violations
+            .iter()
+            .map(|v| (v.record, v.rule))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "user-accounts"),
+                (2, "unique-tx-id"),
+                (3, "timestamp-range")
+            ]
+        );
+        assert_eq!(
+            report.violations[1].to_string(),
+            "record 2 (TX_ID 1): [unique-tx-id] duplicate TX_ID, first seen in record 1"
+        );
+    }
+
+    #[test]
+    fn test_custom_rule_set() {
+        let records = [transfer(1, 10, 10), transfer(1, 10, 20)];
+        let report = Validator::new()
+            .rule(UniqueTxIdRule::default())
+            .validate(&records);
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].rule, "unique-tx-id");
+    }
+}